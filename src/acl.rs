@@ -0,0 +1,6181 @@
+use bitflags::bitflags;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json;
+use near_sdk::{env, require, AccountId, BorshStorageKey, FunctionError, IntoStorageKey};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Roles are represented by enum variants.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[repr(u8)]
+pub enum Role {
+    L1,
+    L2,
+    L3,
+}
+
+/// Represents admin permissions for roles. Variant `Super` grants global admin
+/// permissions, each following variant grants admin permissions for the `Role`
+/// with the corresponding name.
+#[derive(Copy, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[repr(u8)]
+pub enum AclAdmin {
+    Super,
+    L1,
+    L2,
+    L3,
+}
+
+impl From<Role> for AclAdmin {
+    fn from(value: Role) -> Self {
+        match value {
+            Role::L1 => AclAdmin::L1,
+            Role::L2 => AclAdmin::L2,
+            Role::L3 => AclAdmin::L3,
+        }
+    }
+}
+
+impl Role {
+    /// Returns all variants of `Role`, ordered by discriminant.
+    pub fn all() -> [Role; 3] {
+        [Role::L1, Role::L2, Role::L3]
+    }
+
+    /// Returns the `AclAdmin` variant responsible for a `Role`.
+    fn admin(self) -> AclAdmin {
+        AclAdmin::from(self)
+    }
+}
+
+/// A role that can be managed by an [`Acl`]. Implementing it for a custom enum
+/// allows reusing `Acl` with other roles than [`Role`].
+///
+/// The flags of a role and of its admin must be distinct from each other, from
+/// [`AclPermissions::SUPER_ADMIN`] and from the flags of other roles.
+pub trait AclRole:
+    Copy + fmt::Debug + PartialEq + Into<AclPermissions> + BorshDeserialize + BorshSerialize + Serialize
+{
+    /// Returns every role, ordered by discriminant.
+    fn all_roles() -> Vec<Self>;
+
+    /// Returns the flag of the admins for the role.
+    fn admin_flag(self) -> AclPermissions;
+}
+
+impl AclRole for Role {
+    fn all_roles() -> Vec<Self> {
+        Role::all().to_vec()
+    }
+
+    fn admin_flag(self) -> AclPermissions {
+        self.admin().into()
+    }
+}
+
+impl TryFrom<u8> for Role {
+    type Error = AclError;
+
+    /// Returns the `Role` with discriminant `value`.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Role::all()
+            .into_iter()
+            .find(|role| *role as u8 == value)
+            .ok_or(AclError::InvalidRoleDiscriminant { value })
+    }
+}
+
+bitflags! {
+    /// Flags that represent permissions in a bitmask.
+    ///
+    /// If a flag's binary value is `1 << n` with even `n` it represents an
+    /// `AclAdmin` role. Otherwise (`n` is odd), the flag represents a regular
+    /// `Role`.
+    ///
+    /// Bitmasks allow efficiently checking for multiple permissions.
+    #[derive(BorshDeserialize, BorshSerialize)]
+    pub struct AclPermissions: u128 {
+        const SUPER_ADMIN = 0b00000001; // 01u128 == 1 << 0
+        const L1 = 0b00000010;          // 02u128 == 1 << 1
+        const L1_ADMIN = 0b00000100;    // 04u128 == 1 << 2
+        const L2 = 0b00001000;          // 08u128 == 1 << 3
+        const L2_ADMIN = 0b00010000;    // 16u128 == 1 << 4
+        const L3 = 0b00100000;          // 32u128 == 1 << 5
+        const L3_ADMIN = 0b01000000;    // 64u128 == 1 << 6
+    }
+}
+
+const MAX_BITFLAG_SHIFT: u8 = 127; // `AclPermissions` is u128
+
+impl fmt::Display for AclPermissions {
+    /// Formats the set flags in ascending bit order, e.g. `SUPER_ADMIN | L1 | L3_ADMIN`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut flags = AclPermissions::iter_defined().filter(|flag| self.contains(*flag));
+        match flags.next() {
+            Some(first) => {
+                write!(f, "{:?}", first)?;
+                for flag in flags {
+                    write!(f, " | {:?}", flag)?;
+                }
+                Ok(())
+            }
+            None => write!(f, "(empty)"),
+        }
+    }
+}
+
+impl From<Role> for AclPermissions {
+    /// Panics if there is no flag for `value`, see
+    /// [`AclPermissions::try_from_role`].
+    fn from(value: Role) -> Self {
+        AclPermissions::try_from_role(value).unwrap_or_else(|err| err.panic())
+    }
+}
+
+impl From<AclAdmin> for AclPermissions {
+    /// Panics if there is no flag for `value`, see
+    /// [`AclPermissions::try_from_admin`].
+    fn from(value: AclAdmin) -> Self {
+        AclPermissions::try_from_admin(value).unwrap_or_else(|err| err.panic())
+    }
+}
+
+impl AclPermissions {
+    /// Maximum number of roles the bit layout can represent. The flags of role
+    /// `n` (counting from zero) and of its admin are at positions `2n + 1` and
+    /// `2n + 2`, which leaves room for 63 roles besides `SUPER_ADMIN` in a
+    /// `u128`.
+    pub const MAX_ROLES: u8 = MAX_BITFLAG_SHIFT / 2;
+
+    /// Returns the flag of `role`, or an error instead of panicking if no flag
+    /// is defined for it. This is the case if `role` exceeds
+    /// [`Self::MAX_ROLES`] or its flag is missing in the definition of
+    /// `AclPermissions`.
+    pub fn try_from_role(role: Role) -> Result<Self, AclError> {
+        // `+1` since flags for `Role` have a bit shifted by an odd number.
+        let shift = role as u8 * 2 + 1;
+        Self::try_from_defined_shift(shift)
+    }
+
+    /// Like [`Self::try_from_role`], but returns the flag of `admin`.
+    pub fn try_from_admin(admin: AclAdmin) -> Result<Self, AclError> {
+        // Flags for `AclAdmin` have a bit shifted by an even number.
+        let shift = admin as u8 * 2;
+        Self::try_from_defined_shift(shift)
+    }
+
+    /// Returns the flag `1 << shift` if it is defined in `AclPermissions`.
+    fn try_from_defined_shift(shift: u8) -> Result<Self, AclError> {
+        if shift > MAX_BITFLAG_SHIFT {
+            return Err(AclError::UndefinedFlag { shift });
+        }
+        AclPermissions::from_bits(1u128 << shift).ok_or(AclError::UndefinedFlag { shift })
+    }
+
+    /// Returns an iterator over every defined flag, in ascending bit order.
+    fn iter_defined() -> impl Iterator<Item = AclPermissions> {
+        (0..=MAX_BITFLAG_SHIFT).filter_map(|shift| AclPermissions::from_bits(1u128 << shift))
+    }
+
+    /// Returns the roles whose flag is set, ordered by discriminant.
+    pub fn roles<R: AclRole>(self) -> Vec<R> {
+        R::all_roles()
+            .into_iter()
+            .filter(|role| self.contains((*role).into()))
+            .collect()
+    }
+
+    /// Returns the roles whose admin flag is set, ordered by discriminant.
+    /// Roles administered via `SUPER_ADMIN` are not included unless their
+    /// admin flag is set, too.
+    pub fn admin_roles<R: AclRole>(self) -> Vec<R> {
+        R::all_roles()
+            .into_iter()
+            .filter(|role| self.contains(role.admin_flag()))
+            .collect()
+    }
+
+    /// Returns whether the `SUPER_ADMIN` flag is set.
+    pub fn is_super_admin_flag_set(self) -> bool {
+        self.contains(AclPermissions::SUPER_ADMIN)
+    }
+
+    /// Returns the union of the flags of `roles`.
+    fn from_roles<R: AclRole>(roles: &[R]) -> AclPermissions {
+        roles.iter().fold(AclPermissions::empty(), |flags, role| {
+            flags | (*role).into()
+        })
+    }
+
+    /// Returns how a bearer of these permissions is authorized as an admin for
+    /// `role`, or `None` if it is not an admin for `role`. See
+    /// [`Acl::admin_authorization`].
+    fn admin_authorization<R: AclRole>(self, role: R) -> Option<AclAuthorizedVia> {
+        if self.contains(AclPermissions::SUPER_ADMIN) {
+            Some(AclAuthorizedVia::Super)
+        } else if self.contains(role.admin_flag()) {
+            Some(AclAuthorizedVia::RoleAdmin)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the flag `1 << shift`, which need not be defined in
+    /// `AclPermissions`. Used for roles registered at runtime. Returns an error
+    /// if `shift` exceeds the bits of `AclPermissions`.
+    fn try_from_shift(shift: u8) -> Result<AclPermissions, AclError> {
+        if shift > MAX_BITFLAG_SHIFT {
+            return Err(AclError::UndefinedFlag { shift });
+        }
+        Ok(Self::from_bits_retained(1u128 << shift))
+    }
+
+    /// Returns the permissions with `bits` if all of them are set in `known`.
+    /// Unlike `from_bits`, this accepts bits that are not defined in
+    /// `AclPermissions`, namely the flags of roles registered at runtime,
+    /// which callers pass in `known`.
+    fn from_known_bits(bits: u128, known: AclPermissions) -> Option<AclPermissions> {
+        if bits & !known.bits() != 0 {
+            return None;
+        }
+        Some(Self::from_bits_retained(bits))
+    }
+
+    /// Returns the permissions with `bits`, retaining undefined bits.
+    ///
+    /// Invariant: permissions with undefined bits must only be combined and
+    /// queried via `|`, `&`, `-`, `insert`, `remove`, `contains`,
+    /// `intersects` and `bits`, which operate on all bits. `!` and
+    /// `complement` drop undefined bits and must not be applied to them.
+    fn from_bits_retained(bits: u128) -> AclPermissions {
+        // SAFETY: `from_bits_unchecked` is marked unsafe by bitflags only
+        // because undefined bits may be dropped by some operations, see the
+        // invariant above. No memory safety is involved.
+        unsafe { AclPermissions::from_bits_unchecked(bits) }
+    }
+
+    /// Returns the name of a flag as given in its definition, e.g. `L1_ADMIN`.
+    fn name(self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Determines which accounts have a role.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AclRoleMode {
+    /// Only accounts granted the role have it. This is the default.
+    Allowlist,
+    /// All accounts have the role except those it was revoked from.
+    Denylist,
+}
+
+/// Permissions required to call a guarded method.
+#[derive(Copy, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum AclRequirement {
+    /// At least one of the permissions is required, see [`Acl::check_any`].
+    Any(AclPermissions),
+    /// All of the permissions are required, see [`Acl::check_all`].
+    All(AclPermissions),
+}
+
+impl AclRequirement {
+    /// Returns a requirement for all of `roles` if `require_all` is set,
+    /// otherwise for at least one of them.
+    pub fn from_roles<R: AclRole>(roles: &[R], require_all: bool) -> Self {
+        let target = AclPermissions::from_roles(roles);
+        if require_all {
+            Self::All(target)
+        } else {
+            Self::Any(target)
+        }
+    }
+
+    /// Returns the permissions the requirement refers to.
+    fn target(self) -> AclPermissions {
+        match self {
+            Self::Any(target) | Self::All(target) => target,
+        }
+    }
+
+    /// Returns whether `permissions` satisfy the requirement.
+    fn is_satisfied_by(self, permissions: AclPermissions) -> bool {
+        match self {
+            Self::Any(target) => permissions.intersects(target),
+            Self::All(target) => permissions.contains(target),
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Acl<R> {
+    /// Stores permissions per account.
+    permissions: LookupMap<AccountId, AclPermissions>,
+    /// Number of entries in `permissions`.
+    num_accounts: u64,
+    /// Permissions of the account read last from `permissions`. Not persisted.
+    #[borsh_skip]
+    permissions_cache: AclPermissionsCache,
+    /// Index of the accounts in `permissions`. Only maintained if enumeration
+    /// of accounts was enabled on construction.
+    accounts: Option<UnorderedSet<AccountId>>,
+    /// Stores the set of accounts that bear a permission.
+    bearers: UnorderedMap<AclPermissions, UnorderedSet<AccountId>>,
+    /// If set, enumeration of bearers is restricted to admins.
+    private_enumeration: bool,
+    /// Order in which views list roles. If `None`, roles are listed by
+    /// discriminant.
+    role_display_order: Option<Vec<R>>,
+    /// If set, events include the affected account's permissions before and
+    /// after the mutation.
+    event_masks: bool,
+    /// If set, granting a role together with its admin flag emits a single
+    /// combined event.
+    combine_grant_events: bool,
+    /// Role granted to accounts on their first call of the entry method.
+    auto_grant_role: Option<R>,
+    /// Accounts that have been granted `auto_grant_role`.
+    onboarded: UnorderedSet<AccountId>,
+    /// Stores the requirement guarding a method, keyed by method name.
+    method_requirements: UnorderedMap<String, AclRequirement>,
+    /// Schedules of role grants which are active only within a time window,
+    /// keyed by account and role flag. Grants without schedule are permanent.
+    role_schedules: LookupMap<(AccountId, AclPermissions), AclGrantSchedule>,
+    /// Roles registered at runtime, mapped to the bit position of their flag.
+    /// The flag of the role's admin is at the next higher position.
+    dynamic_roles: UnorderedMap<String, u8>,
+    /// Named groups of roles, mapped to the union of the flags of their
+    /// members.
+    role_groups: UnorderedMap<String, AclPermissions>,
+    /// Roles implied by a role for the purpose of checks, keyed by the flag of
+    /// the implying role. Implications are transitive and free of cycles.
+    role_implications: UnorderedMap<AclPermissions, AclPermissions>,
+    /// Human readable names of roles for display by front-ends.
+    role_labels: UnorderedMap<R, String>,
+    /// Maximum number of bearers of a role's admin flag. Roles without entry
+    /// have no maximum.
+    max_admins: LookupMap<R, u32>,
+    /// Minimum number of bearers of a role's admin flag. Roles without entry
+    /// have no minimum.
+    min_admins: LookupMap<R, u32>,
+    /// Flags of the roles in [`AclRoleMode::Denylist`] mode.
+    denylist_roles: AclPermissions,
+    /// Accounts denied a role in denylist mode, keyed by account and role
+    /// flag.
+    role_denials: UnorderedSet<(AccountId, AclPermissions)>,
+    /// Accounts which may grant a role without being its admin, keyed by
+    /// account and role flag.
+    delegated_admins: UnorderedSet<(AccountId, AclPermissions)>,
+    /// Prefix of the storage keys of all collections of this instance.
+    storage_prefix: Vec<u8>,
+    /// Prepended to the names of emitted events.
+    event_prefix: String,
+    /// Version of emitted events. If `None`, [`EVENT_VERSION`] is used.
+    event_version: Option<String>,
+    /// A proposed super admin which has not yet accepted.
+    super_admin_proposal: Option<SuperAdminProposal>,
+}
+
+/// Caches the permissions of a single account for the duration of a call.
+///
+/// Checked methods typically read the predecessor's permissions more than once,
+/// e.g. for the admin check and again when the predecessor is also the target
+/// or when a super admin check precedes the mutation. Each read from
+/// `Acl::permissions` is a storage read, which is charged per call and per byte
+/// and costs far more gas than keeping the most recent value in memory.
+///
+/// Writes to `Acl::permissions` update the cache, so it never holds stale
+/// permissions. Since the `Acl` is deserialized anew for every call, the cache
+/// does not outlive a call.
+#[derive(Default)]
+struct AclPermissionsCache(RefCell<Option<(AccountId, AclPermissions)>>);
+
+impl AclPermissionsCache {
+    /// Returns the cached permissions if they belong to `account_id`.
+    fn get(&self, account_id: &AccountId) -> Option<AclPermissions> {
+        match self.0.borrow().as_ref() {
+            Some((cached_id, permissions)) if cached_id == account_id => Some(*permissions),
+            _ => None,
+        }
+    }
+
+    /// Caches `permissions` as the current permissions of `account_id`.
+    fn set(&self, account_id: &AccountId, permissions: AclPermissions) {
+        *self.0.borrow_mut() = Some((account_id.clone(), permissions));
+    }
+}
+
+/// An event recorded in the history of an `Acl`.
+#[cfg(feature = "event-history")]
+#[derive(BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StoredAclEvent {
+    /// Position of the event among all recorded events, starting at zero.
+    pub index: U64,
+    /// The event as returned by [`AclEvent::to_json_event_string`], without the
+    /// `EVENT_JSON:` prefix of logged events.
+    pub json: String,
+}
+
+/// Ring buffer of recently emitted events.
+///
+/// Events are emitted by methods that borrow the `Acl` immutably, hence entries
+/// and the number of recorded events are written directly to storage instead
+/// of to collections that are part of the `Acl`. The capacity is stored under
+/// its own key, too, so the layout of `Acl` does not depend on feature
+/// `event-history`.
+#[cfg(feature = "event-history")]
+struct AclEventHistory {
+    /// Prefix of the storage keys of entries.
+    entries_prefix: Vec<u8>,
+    /// Storage key of the number of events recorded since the last clear.
+    length_key: Vec<u8>,
+    /// Storage key of the maximum number of stored entries.
+    capacity_key: Vec<u8>,
+}
+
+#[cfg(feature = "event-history")]
+impl AclEventHistory {
+    fn new(acl_prefix: &[u8]) -> Self {
+        Self {
+            entries_prefix: acl_new_storage_prefix(acl_prefix, AclStorageKey::EventHistory),
+            length_key: acl_new_storage_prefix(acl_prefix, AclStorageKey::EventHistoryLength),
+            capacity_key: acl_new_storage_prefix(acl_prefix, AclStorageKey::EventHistoryCapacity),
+        }
+    }
+
+    /// Returns the maximum number of stored entries. Zero, which is the
+    /// default, disables recording.
+    fn capacity(&self) -> u64 {
+        Self::read_u64(&self.capacity_key)
+    }
+
+    /// Sets the maximum number of stored entries and clears the history.
+    fn set_capacity(&self, capacity: u64) {
+        self.clear();
+        env::storage_write(&self.capacity_key, &capacity.to_le_bytes());
+    }
+
+    /// Returns the number of events recorded since the last clear.
+    fn length(&self) -> u64 {
+        Self::read_u64(&self.length_key)
+    }
+
+    /// Reads the `u64` stored under `key`, which defaults to zero.
+    fn read_u64(key: &[u8]) -> u64 {
+        match env::storage_read(key) {
+            Some(bytes) => u64::try_from_slice(&bytes)
+                .unwrap_or_else(|_| env::panic_str("Failed to read event history")),
+            None => 0,
+        }
+    }
+
+    fn entry_key(&self, index: u64, capacity: u64) -> Vec<u8> {
+        let slot = index % capacity;
+        [self.entries_prefix.as_slice(), &slot.to_le_bytes()].concat()
+    }
+
+    /// Stores `event`, overwriting the oldest entry if the history is full.
+    fn record<T: Serialize>(&self, event: &AclEvent<T>) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+        let index = self.length();
+        let entry = StoredAclEvent {
+            index: index.into(),
+            json: event.to_json_event_string(),
+        };
+        let entry = entry
+            .try_to_vec()
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize StoredAclEvent"));
+        env::storage_write(&self.entry_key(index, capacity), &entry);
+        env::storage_write(&self.length_key, &(index + 1).to_le_bytes());
+    }
+
+    /// Returns up to `limit` of the stored entries, oldest first, skipping the
+    /// `skip` oldest ones.
+    fn get(&self, skip: usize, limit: usize) -> Vec<StoredAclEvent> {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return vec![];
+        }
+        let length = self.length();
+        let oldest = length.saturating_sub(capacity);
+        (oldest.saturating_add(skip as u64)..length)
+            .take(limit)
+            .map(|index| {
+                let bytes = env::storage_read(&self.entry_key(index, capacity))
+                    .unwrap_or_else(|| env::panic_str("Missing event history entry"));
+                StoredAclEvent::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str("Failed to read event history entry"))
+            })
+            .collect()
+    }
+
+    /// Removes all entries.
+    fn clear(&self) {
+        let capacity = self.capacity();
+        if capacity > 0 {
+            let length = self.length();
+            for index in length.saturating_sub(capacity)..length {
+                env::storage_remove(&self.entry_key(index, capacity));
+            }
+        }
+        env::storage_remove(&self.length_key);
+    }
+}
+
+/// Default value of `Acl::storage_prefix`. Not to be used directly. Create
+/// prefixes with [`acl_new_storage_prefix`].
+const ACL_STORAGE_PREFIX: &[u8; 4] = b"_acl";
+
+/// Returns a new prefix by appending `specifier` to `base`.
+fn acl_new_storage_prefix(base: &[u8], specifier: AclStorageKey) -> Vec<u8> {
+    [base, specifier.into_storage_key().as_slice()].concat()
+}
+
+// Estimates of the storage used by the ACL. They include the 40 bytes the
+// runtime charges per record in addition to key and value, and assume account
+// ids of maximum length.
+
+/// Estimated bytes per entry in `Acl::permissions`, which stores an account in
+/// one record.
+const ACL_STORAGE_BYTES_PER_ACCOUNT: u64 = 130;
+/// Estimated bytes per entry in an `UnorderedSet` of accounts like `Acl::accounts`
+/// or a set of `Acl::bearers`, which stores an account in two records.
+const ACL_STORAGE_BYTES_PER_BEARER: u64 = 280;
+/// Estimated bytes used independently of the number of accounts, e.g. by the
+/// entries of `Acl::bearers` and by method requirements.
+const ACL_STORAGE_BYTES_OVERHEAD: u64 = 2_000;
+
+/// Maximum number of accounts that can be checked in one batch.
+const ACL_MAX_BATCH_SIZE: usize = 100;
+
+/// Maximum number of items that can be requested per page of an enumeration.
+const ACL_MAX_PAGE_SIZE: usize = 100;
+
+/// Used to make storage prefixes unique. Every collection of an `Acl` has its
+/// own variant, which is appended to `Acl::storage_prefix`. New variants must
+/// be added at the end to keep the keys of existing collections stable.
+#[derive(BorshSerialize, BorshStorageKey)]
+enum AclStorageKey {
+    Permissions,
+    Bearers,
+    BearersSet {
+        permission: AclPermissions,
+    },
+    Onboarded,
+    MethodRequirements,
+    DynamicRoles,
+    RoleSchedules,
+    Accounts,
+    RoleGroups,
+    RoleImplications,
+    RoleLabels,
+    MaxAdmins,
+    MinAdmins,
+    RoleDenials,
+    #[cfg_attr(not(feature = "event-history"), allow(dead_code))]
+    EventHistory,
+    #[cfg_attr(not(feature = "event-history"), allow(dead_code))]
+    EventHistoryLength,
+    #[cfg_attr(not(feature = "event-history"), allow(dead_code))]
+    EventHistoryCapacity,
+    DelegatedAdmins,
+}
+
+// Layout of `Acl` before the state version was tracked, see
+// `Counter::migrate`. It must not be changed, since it describes state that has
+// been written.
+
+/// Layout of [`Acl`] in state version 0. Permissions were stored in an
+/// `UnorderedMap`.
+#[derive(BorshDeserialize)]
+pub struct AclV0 {
+    permissions: UnorderedMap<AccountId, AclPermissions>,
+    bearers: UnorderedMap<AclPermissions, UnorderedSet<AccountId>>,
+}
+
+impl<R: AclRole> From<AclV0> for Acl<R> {
+    /// Moves the permissions into a `LookupMap` of an enumerable `Acl`, since
+    /// permissions could be enumerated in version 0. The sets of bearers are
+    /// kept in place. Fields added later are initialized like in
+    /// [`Acl::new_enumerable`].
+    ///
+    /// All accounts are moved in a single call, which must not exceed the gas
+    /// limit.
+    fn from(old: AclV0) -> Self {
+        let AclV0 {
+            mut permissions,
+            bearers,
+        } = old;
+        let entries: Vec<(AccountId, AclPermissions)> = permissions.iter().collect();
+        permissions.clear();
+
+        let mut acl = Self {
+            bearers,
+            ..Self::new_enumerable()
+        };
+        for (account_id, account_permissions) in entries.iter() {
+            acl.store_permissions(account_id, account_permissions);
+        }
+        acl
+    }
+}
+
+impl<R: AclRole> Default for Acl<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: AclRole> Acl<R> {
+    pub fn new() -> Self {
+        Self::with_prefix(ACL_STORAGE_PREFIX.to_vec(), false)
+    }
+
+    /// Like [`Self::new`], but enables enumeration of accounts, see
+    /// [`Self::with_prefix`].
+    pub fn new_enumerable() -> Self {
+        Self::with_prefix(ACL_STORAGE_PREFIX.to_vec(), true)
+    }
+
+    /// Creates an `Acl` whose collections are stored under `prefix`. Multiple
+    /// instances in one contract must use distinct prefixes, none of which may
+    /// be a prefix of another.
+    ///
+    /// If `enumerable` is set, an index of all accounts with permissions is
+    /// maintained, which is required by [`Self::export_permissions`] and
+    /// [`Self::get_authorized_accounts`]. It costs additional storage per
+    /// account. Bearers of a permission can be enumerated in any case.
+    ///
+    /// Panics if `prefix` is empty.
+    pub fn with_prefix(prefix: Vec<u8>, enumerable: bool) -> Self {
+        require!(!prefix.is_empty(), "ACL storage prefix must not be empty");
+        Self::assert_role_layout();
+        Self {
+            permissions: LookupMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::Permissions,
+            )),
+            num_accounts: 0,
+            permissions_cache: AclPermissionsCache::default(),
+            accounts: if enumerable {
+                Some(UnorderedSet::new(acl_new_storage_prefix(
+                    &prefix,
+                    AclStorageKey::Accounts,
+                )))
+            } else {
+                None
+            },
+            bearers: UnorderedMap::new(acl_new_storage_prefix(&prefix, AclStorageKey::Bearers)),
+            private_enumeration: false,
+            role_display_order: None,
+            event_masks: false,
+            combine_grant_events: false,
+            auto_grant_role: None,
+            onboarded: UnorderedSet::new(acl_new_storage_prefix(&prefix, AclStorageKey::Onboarded)),
+            method_requirements: UnorderedMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::MethodRequirements,
+            )),
+            role_schedules: LookupMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::RoleSchedules,
+            )),
+            dynamic_roles: UnorderedMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::DynamicRoles,
+            )),
+            role_groups: UnorderedMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::RoleGroups,
+            )),
+            role_implications: UnorderedMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::RoleImplications,
+            )),
+            role_labels: UnorderedMap::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::RoleLabels,
+            )),
+            max_admins: LookupMap::new(acl_new_storage_prefix(&prefix, AclStorageKey::MaxAdmins)),
+            min_admins: LookupMap::new(acl_new_storage_prefix(&prefix, AclStorageKey::MinAdmins)),
+            denylist_roles: AclPermissions::empty(),
+            role_denials: UnorderedSet::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::RoleDenials,
+            )),
+            delegated_admins: UnorderedSet::new(acl_new_storage_prefix(
+                &prefix,
+                AclStorageKey::DelegatedAdmins,
+            )),
+            storage_prefix: prefix,
+            event_prefix: EVENT_PREFIX.to_string(),
+            event_version: None,
+            super_admin_proposal: None,
+        }
+    }
+
+    /// Panics unless every role and its admin map to flags defined in
+    /// [`AclPermissions`] which are distinct from each other and from
+    /// [`AclPermissions::SUPER_ADMIN`]. Fails loudly on construction instead of
+    /// leaving a role that cannot be administered.
+    fn assert_role_layout() {
+        let mut used_flags = AclPermissions::SUPER_ADMIN;
+        for role in R::all_roles() {
+            // The conversions panic if a flag is not defined.
+            let role_flag: AclPermissions = role.into();
+            let admin_flag: AclPermissions = role.admin_flag();
+            require!(
+                role_flag != admin_flag && !used_flags.intersects(role_flag | admin_flag),
+                format!("Flags of Role {:?} collide with other flags", role),
+            );
+            used_flags |= role_flag | admin_flag;
+        }
+    }
+
+    fn new_bearers_set(&self, permission: AclPermissions) -> UnorderedSet<AccountId> {
+        UnorderedSet::new(acl_new_storage_prefix(
+            &self.storage_prefix,
+            AclStorageKey::BearersSet { permission },
+        ))
+    }
+
+    /// Returns an error if `account_id` must not be granted permissions. These
+    /// are the contract's own account, which cannot call its methods as
+    /// predecessor unless via a promise of the contract itself, and the
+    /// `system` account.
+    fn check_grantable(account_id: &AccountId) -> Result<(), AclError> {
+        if *account_id == env::current_account_id() {
+            return Err(AclError::GrantToContract);
+        }
+        if account_id.as_str() == "system" {
+            return Err(AclError::GrantToSystemAccount);
+        }
+        Ok(())
+    }
+
+    /// Panics if `account_id` must not be granted permissions, see
+    /// [`Self::check_grantable`].
+    fn assert_grantable(account_id: &AccountId) {
+        Self::require_ok(Self::check_grantable(account_id));
+    }
+
+    /// Panics with the message of the error in `result`, if any. Like a failed
+    /// `require!`, and unlike [`AclError::panic`], the panic can be caught by
+    /// unit tests.
+    fn require_ok(result: Result<(), AclError>) {
+        if let Err(err) = result {
+            require!(false, err.to_string());
+        }
+    }
+
+    /// Returns the permissions stored for `account_id`. If there are no
+    /// permissions stored for `account_id`, it returns an empty, newly
+    /// initialized set of permissions.
+    ///
+    /// Roles in denylist mode are not included, since they are held without
+    /// being stored. Checks of roles must read [`Self::get_active_permissions`]
+    /// or [`Self::get_effective_permissions`] instead, which include them for
+    /// unknown accounts, too.
+    ///
+    /// The permissions of the account read last are cached, see
+    /// [`AclPermissionsCache`].
+    fn get_or_init_permissions(&self, account_id: &AccountId) -> AclPermissions {
+        if let Some(permissions) = self.permissions_cache.get(account_id) {
+            return permissions;
+        }
+        let permissions = match self.permissions.get(account_id) {
+            Some(permissions) => permissions,
+            None => AclPermissions::empty(),
+        };
+        self.permissions_cache.set(account_id, permissions);
+        permissions
+    }
+
+    /// Stores `permissions` for `account_id`, updating the account index. If
+    /// `permissions` are empty, the entry of `account_id` is removed instead,
+    /// so that only accounts with permissions occupy storage and are
+    /// enumerated.
+    fn store_permissions(&mut self, account_id: &AccountId, permissions: &AclPermissions) {
+        if permissions.is_empty() {
+            self.remove_permissions(account_id);
+            return;
+        }
+        self.permissions_cache.set(account_id, *permissions);
+        if self.permissions.insert(account_id, permissions).is_none() {
+            self.num_accounts += 1;
+            if let Some(accounts) = self.accounts.as_mut() {
+                accounts.insert(account_id);
+            }
+        }
+    }
+
+    /// Removes the entry of `account_id`, updating the account index.
+    fn remove_permissions(&mut self, account_id: &AccountId) {
+        self.permissions_cache
+            .set(account_id, AclPermissions::empty());
+        if self.permissions.remove(account_id).is_some() {
+            self.num_accounts -= 1;
+            if let Some(accounts) = self.accounts.as_mut() {
+                accounts.remove(account_id);
+            }
+        }
+    }
+
+    /// Returns the permissions of `account_id` without role grants that are not
+    /// active at the current block timestamp, and with the roles in denylist
+    /// mode which `account_id` has not been denied. Reads the schedule of every
+    /// role `account_id` has been granted.
+    fn get_active_permissions(&self, account_id: &AccountId) -> AclPermissions {
+        let mut permissions = self.get_or_init_permissions(account_id);
+        let now = env::block_timestamp();
+        for role in R::all_roles() {
+            let flag: AclPermissions = role.into();
+            if !permissions.contains(flag) {
+                continue;
+            }
+            if let Some(schedule) = self.role_schedules.get(&(account_id.clone(), flag)) {
+                if !schedule.is_active_at(now) {
+                    permissions.remove(flag);
+                }
+            }
+        }
+        for role in R::all_roles() {
+            let flag: AclPermissions = role.into();
+            if self.denylist_roles.contains(flag)
+                && !permissions.contains(flag)
+                && !self.is_role_denied(account_id, flag)
+            {
+                permissions.insert(flag);
+            }
+        }
+        permissions
+    }
+
+    /// Returns the active permissions of `account_id` extended by the roles
+    /// implied by its roles and, for a super admin, by the admin flags of all
+    /// roles. Checks evaluate requirements against these.
+    fn get_effective_permissions(&self, account_id: &AccountId) -> AclPermissions {
+        let mut permissions = self.expand_implications(self.get_active_permissions(account_id));
+        if permissions.contains(AclPermissions::SUPER_ADMIN) {
+            for role in R::all_roles() {
+                permissions.insert(role.admin_flag());
+            }
+        }
+        permissions
+    }
+
+    /// Returns `permissions` extended by the roles which are implied, directly
+    /// or transitively, by the roles in `permissions`.
+    fn expand_implications(&self, permissions: AclPermissions) -> AclPermissions {
+        if self.role_implications.is_empty() {
+            return permissions;
+        }
+        let mut expanded = permissions;
+        let mut pending = permissions;
+        while !pending.is_empty() {
+            let mut newly_implied = AclPermissions::empty();
+            for role in R::all_roles() {
+                let flag: AclPermissions = role.into();
+                if !pending.contains(flag) {
+                    continue;
+                }
+                if let Some(implied) = self.role_implications.get(&flag) {
+                    newly_implied |= implied - expanded;
+                }
+            }
+            expanded |= newly_implied;
+            pending = newly_implied;
+        }
+        expanded
+    }
+
+    /// Removes the schedule of the grant of `flag` to `account_id`, making a
+    /// grant permanent. Returns whether the grant was inactive according to
+    /// the removed schedule.
+    fn clear_role_schedule(&mut self, account_id: &AccountId, flag: AclPermissions) -> bool {
+        match self.role_schedules.remove(&(account_id.clone(), flag)) {
+            Some(schedule) => !schedule.is_active_at(env::block_timestamp()),
+            None => false,
+        }
+    }
+
+    /// Returns a `bool` indicating if `account_id` is an admin for `role`.
+    ///
+    /// Note that `AclAdmin::Super` grants admin rights for _every_ role. Hence,
+    /// if `account_id` has the corresponding permissions
+    /// [`AclPermissions::SUPER_ADMIN`], this function returns true for every
+    /// role.
+    pub fn is_admin(&self, role: R, account_id: &AccountId) -> bool {
+        self.admin_authorization(role, account_id).is_some()
+    }
+
+    /// Returns how `account_id` is authorized as an admin for `role`, or `None`
+    /// if it is not an admin for `role`.
+    ///
+    /// If `account_id` is both a super admin and an admin for `role`, the
+    /// authorization is attributed to [`AclAuthorizedVia::Super`]. Revoking the
+    /// role specific admin flag would not affect such an account's authority.
+    fn admin_authorization(&self, role: R, account_id: &AccountId) -> Option<AclAuthorizedVia> {
+        self.get_or_init_permissions(account_id)
+            .admin_authorization(role)
+    }
+
+    /// Returns how `predecessor` is authorized as an admin for `role`
+    /// together with the current permissions of `account_id`, or `None` if
+    /// `predecessor` is not an admin for `role`. Events emitted with the
+    /// returned context name `predecessor` as the predecessor.
+    ///
+    /// If `account_id` is the predecessor, its permissions are read only once.
+    fn authorize_admin_for(
+        &self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Option<(AclEventContext, AclPermissions)> {
+        let predecessor_permissions = self.get_or_init_permissions(predecessor);
+        let authorized_via = predecessor_permissions.admin_authorization(role)?;
+        let permissions = if account_id == predecessor {
+            predecessor_permissions
+        } else {
+            self.get_or_init_permissions(account_id)
+        };
+        let context = AclEventContext::authorized(authorized_via).with_predecessor(predecessor);
+        Some((context, permissions))
+    }
+
+    /// Like [`Self::authorize_admin_for`], but also authorizes a predecessor
+    /// which is a delegated admin for `role`. Used by methods that grant
+    /// `role`, see [`Self::set_delegated_admin`].
+    fn authorize_grantor_for(
+        &self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Option<(AclEventContext, AclPermissions)> {
+        let authorized_via = self.grantor_authorization(role, predecessor)?;
+        Some((
+            AclEventContext::authorized(authorized_via).with_predecessor(predecessor),
+            self.get_or_init_permissions(account_id),
+        ))
+    }
+
+    /// Returns how `account_id` is authorized to grant `role`, or `None` if it
+    /// is neither an admin nor a delegated admin for `role`.
+    fn grantor_authorization(&self, role: R, account_id: &AccountId) -> Option<AclAuthorizedVia> {
+        self.admin_authorization(role, account_id).or_else(|| {
+            self.is_delegated_admin(role, account_id)
+                .then_some(AclAuthorizedVia::Delegated)
+        })
+    }
+
+    /// Makes `account_id` a delegated admin for `role` if `delegated` is set,
+    /// otherwise removes it from the delegated admins, given that the
+    /// predecessor is an admin for `role`. Returns `Some(bool)` indicating
+    /// whether the delegation changed.
+    ///
+    /// A delegated admin may grant `role` like an admin, but it can neither
+    /// revoke `role` nor add admins for `role`. Delegation is independent of
+    /// the permissions of `account_id`.
+    ///
+    /// If the predecessor is not an admin for `role`, the delegation is not
+    /// modified and `None` is returned.
+    pub fn set_delegated_admin(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        delegated: bool,
+    ) -> Option<bool> {
+        self.admin_authorization(role, &env::predecessor_account_id())?;
+        let key = (account_id.clone(), role.into());
+        if delegated {
+            Self::assert_grantable(account_id);
+            Some(self.delegated_admins.insert(&key))
+        } else {
+            Some(self.delegated_admins.remove(&key))
+        }
+    }
+
+    /// Returns whether `account_id` is a delegated admin for `role`, see
+    /// [`Self::set_delegated_admin`]. Admins for `role` are not delegated
+    /// admins unless they were made one explicitly.
+    pub fn is_delegated_admin(&self, role: R, account_id: &AccountId) -> bool {
+        self.delegated_admins
+            .contains(&(account_id.clone(), role.into()))
+    }
+
+    /// Returns the error for a `predecessor` which is not authorized as an
+    /// admin for `role`.
+    fn not_admin_error(role: R, predecessor: &AccountId) -> AclError {
+        AclError::NotAdmin {
+            role: format!("{:?}", role),
+            predecessor: predecessor.clone(),
+        }
+    }
+
+    /// Returns whether `account_id` is a super admin.
+    ///
+    /// Unlike [`Self::is_admin`], this returns false for accounts which are
+    /// admin only for specific roles.
+    pub fn is_super_admin(&self, account_id: &AccountId) -> bool {
+        self.get_or_init_permissions(account_id)
+            .contains(AclPermissions::SUPER_ADMIN)
+    }
+
+    /// Returns whether `account_id` is a super admin or an admin for at least
+    /// one of `roles`. Returns `false` if `roles` is empty, unless `account_id`
+    /// is a super admin.
+    pub fn is_admin_of_any(&self, roles: &[R], account_id: &AccountId) -> bool {
+        let admin_flags = roles
+            .iter()
+            .fold(AclPermissions::SUPER_ADMIN, |flags, role| {
+                flags | role.admin_flag()
+            });
+        self.get_or_init_permissions(account_id)
+            .intersects(admin_flags)
+    }
+
+    /// Returns whether `account_id` is a super admin or an admin for at least
+    /// one role.
+    fn is_admin_of_any_role(&self, account_id: &AccountId) -> bool {
+        self.is_admin_of_any(&R::all_roles(), account_id)
+    }
+
+    /// Adds `account_id` the of admins for `role`, given that `predecessor` is
+    /// an admin for `role`. Returns `Some(bool)` indicating whether
+    /// `account_id` has gained new admin permissions.
+    ///
+    /// Contracts pass `env::predecessor_account_id()` as `predecessor`. Taking
+    /// it as a parameter keeps the check independent of the environment.
+    ///
+    /// If `predecessor` is not and admin for `role`, `account_id` is not
+    /// added to the set of admins and `None` is returned. The same holds if
+    /// `account_id` cannot be granted permissions or if `role` has reached its
+    /// maximum number of admins, see [`Self::set_max_admins`]. Use
+    /// [`Self::try_add_admin`] to tell these cases apart.
+    pub fn add_admin(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Option<bool> {
+        self.try_add_admin(role, account_id, predecessor).ok()
+    }
+
+    /// Like [`Self::add_admin`], but returns an error instead of `None`:
+    /// [`AclError::NotAdmin`] if `predecessor` is not an admin for `role`,
+    /// [`AclError::GrantToContract`] or [`AclError::GrantToSystemAccount`] if
+    /// `account_id` cannot be granted permissions and
+    /// [`AclError::MaxAdminsReached`] if `role` has reached its maximum number
+    /// of admins.
+    pub fn try_add_admin(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Result<bool, AclError> {
+        Self::check_grantable(account_id)?;
+        let (context, permissions) = self
+            .authorize_admin_for(role, account_id, predecessor)
+            .ok_or_else(|| Self::not_admin_error(role, predecessor))?;
+        if !permissions.contains(role.admin_flag()) {
+            self.check_below_max_admins(role)?;
+        }
+        Ok(self.add_admin_internal(role, account_id, permissions, &context))
+    }
+
+    /// Like [`Self::add_admin`], but records `memo` in the emitted event.
+    pub fn add_admin_with_memo(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        memo: String,
+    ) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        let (context, permissions) =
+            self.authorize_admin_for(role, account_id, &env::predecessor_account_id())?;
+        let context = context.with_memo(memo);
+        Some(self.add_admin_internal(role, account_id, permissions, &context))
+    }
+
+    /// Grants admin permissions for `role` to `account_id`, __without__
+    /// checking permissions of the predecessor.
+    ///
+    /// Returns whether `account_id` was newly added to the admins for `role`.
+    pub fn add_admin_unchecked(&mut self, role: R, account_id: &AccountId) -> bool {
+        self.add_admin_internal(
+            role,
+            account_id,
+            self.get_or_init_permissions(account_id),
+            &AclEventContext::default(),
+        )
+    }
+
+    /// Grants admin permissions for `role` to `account_id`, whose current
+    /// permissions are `permissions`. The `context` is recorded in the emitted
+    /// event.
+    fn add_admin_internal(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        permissions: AclPermissions,
+        context: &AclEventContext,
+    ) -> bool {
+        if !permissions.contains(role.admin_flag()) {
+            self.assert_below_max_admins(role);
+        }
+        self.add_admin_flag_internal(role.admin_flag(), role, account_id, permissions, context)
+    }
+
+    /// Inserts the admin `flag` into the `permissions` of `account_id`. The
+    /// emitted event names the role as `role`, which allows roles registered
+    /// at runtime to share this with the roles of `R`.
+    fn add_admin_flag_internal<T: Serialize>(
+        &mut self,
+        flag: AclPermissions,
+        role: T,
+        account_id: &AccountId,
+        mut permissions: AclPermissions,
+        context: &AclEventContext,
+    ) -> bool {
+        let is_new_admin = !permissions.contains(flag);
+        if is_new_admin {
+            let before = permissions;
+            permissions.insert(flag);
+            self.store_permissions(account_id, &permissions);
+            self.add_bearer(flag, account_id);
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(
+                        &self.event_prefix,
+                        AclEventId::AdminAdded,
+                        role,
+                        account_id,
+                    )
+                    .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        is_new_admin
+    }
+
+    /// Sets the maximum number of admins of `role`, given that the predecessor
+    /// is a super admin. Passing `None` removes the maximum. Returns
+    /// `Some(bool)` indicating whether the setting changed.
+    ///
+    /// Admins are counted like in [`Self::count_admins`], so super admins
+    /// count only if they hold the admin flag of `role`. Existing admins are
+    /// kept if there are more than `max` of them, but no further admins can be
+    /// added.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_max_admins(&mut self, role: R, max: Option<u32>) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let previous = match max {
+            Some(max) => self.max_admins.insert(&role, &max),
+            None => self.max_admins.remove(&role),
+        };
+        Some(previous != max)
+    }
+
+    /// Returns the maximum number of admins of `role`, if one is set.
+    pub fn max_admins(&self, role: R) -> Option<u32> {
+        self.max_admins.get(&role)
+    }
+
+    /// Panics if `role` has as many admins as its maximum allows.
+    fn assert_below_max_admins(&self, role: R) {
+        Self::require_ok(self.check_below_max_admins(role));
+    }
+
+    /// Returns an error if `role` has reached its maximum number of admins.
+    fn check_below_max_admins(&self, role: R) -> Result<(), AclError> {
+        match self.max_admins.get(&role) {
+            Some(max) if self.count_admins(role) >= u64::from(max) => {
+                Err(AclError::MaxAdminsReached {
+                    role: format!("{:?}", role),
+                    max,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the minimum number of admins of `role`, given that the predecessor
+    /// is a super admin. Passing `None` removes the minimum. Returns
+    /// `Some(bool)` indicating whether the setting changed.
+    ///
+    /// Admins are counted like in [`Self::count_admins`]. Super admins
+    /// administer every role regardless, so a minimum ensures that a role
+    /// keeps admins of its own, which continue to manage it even if super
+    /// admins step down. Revoking or renouncing admin permissions, including via
+    /// [`Self::revoke_all`], panics if it would leave fewer than `min` admins.
+    /// Transfers to accounts which are not yet admins keep the number of
+    /// admins and are not restricted.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_min_admins(&mut self, role: R, min: Option<u32>) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let previous = match min {
+            Some(min) => self.min_admins.insert(&role, &min),
+            None => self.min_admins.remove(&role),
+        };
+        Some(previous != min)
+    }
+
+    /// Returns the minimum number of admins of `role`, if one is set.
+    pub fn min_admins(&self, role: R) -> Option<u32> {
+        self.min_admins.get(&role)
+    }
+
+    /// Panics if removing the admin flag of `role` from an account with
+    /// `permissions` would leave `role` with fewer admins than its minimum.
+    fn assert_above_min_admins(&self, role: R, permissions: AclPermissions) {
+        Self::require_ok(self.check_above_min_admins(role, permissions));
+    }
+
+    /// Returns an error if removing the admin flag of `role` from an account
+    /// with `permissions` would leave `role` with fewer admins than its
+    /// minimum.
+    fn check_above_min_admins(&self, role: R, permissions: AclPermissions) -> Result<(), AclError> {
+        if !permissions.contains(role.admin_flag()) {
+            return Ok(());
+        }
+        match self.min_admins.get(&role) {
+            Some(min) if self.count_admins(role) <= u64::from(min) => {
+                Err(AclError::MinAdminsReached {
+                    role: format!("{:?}", role),
+                    min,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Revoke admin permissions for `role` from `account_id`. If `predecessor`
+    /// is an admin for `role`, it returns `Some<bool>` indicating whether
+    /// `account_id` was an admin. See [`Self::add_admin`] regarding
+    /// `predecessor`.
+    ///
+    /// If `predecessor` is not an admin for `role`, it returns `None`
+    /// permissions are not modified.
+    ///
+    /// `None` is returned as well if `account_id` is a super admin, since it
+    /// would remain admin for `role` via [`AclPermissions::SUPER_ADMIN`], or if
+    /// `role` would be left with fewer admins than its minimum, see
+    /// [`Self::set_min_admins`]. Use [`Self::try_revoke_admin`] to tell these
+    /// cases apart.
+    pub fn revoke_admin(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Option<bool> {
+        self.try_revoke_admin(role, account_id, predecessor).ok()
+    }
+
+    /// Like [`Self::revoke_admin`], but returns an error instead of `None`:
+    /// [`AclError::NotAdmin`] if `predecessor` is not an admin for `role`,
+    /// [`AclError::RedundantRevoke`] if `account_id` is a super admin and
+    /// [`AclError::MinAdminsReached`] if `role` would be left with fewer admins
+    /// than its minimum.
+    pub fn try_revoke_admin(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Result<bool, AclError> {
+        let (context, permissions) = self
+            .authorize_admin_for(role, account_id, predecessor)
+            .ok_or_else(|| Self::not_admin_error(role, predecessor))?;
+        if permissions.contains(AclPermissions::SUPER_ADMIN) {
+            return Err(AclError::RedundantRevoke {
+                account_id: account_id.clone(),
+                role: format!("{:?}", role),
+            });
+        }
+        self.check_above_min_admins(role, permissions)?;
+        Ok(self.revoke_admin_internal(
+            role,
+            account_id,
+            permissions,
+            AclEventId::AdminRevoked,
+            &context,
+        ))
+    }
+
+    /// Moves admin permissions for `role` from `from` to `to`, given that the
+    /// predecessor is an admin for `role`. Returns `Some(bool)` indicating
+    /// whether `from` was an admin for `role`. Otherwise permissions are not
+    /// modified.
+    ///
+    /// Panics if `from` and `to` are the same account. Like
+    /// [`Self::revoke_admin`], it rejects a `from` which is a super admin, since
+    /// it would remain admin for `role` via [`AclPermissions::SUPER_ADMIN`]. If
+    /// `to` is an admin for `role` already, the number of admins decreases,
+    /// hence it panics if that would leave fewer admins than the minimum, see
+    /// [`Self::set_min_admins`]. If the predecessor is not an admin for `role`,
+    /// permissions are not modified and `None` is returned.
+    pub fn transfer_admin(&mut self, role: R, from: &AccountId, to: &AccountId) -> Option<bool> {
+        Self::assert_grantable(to);
+        require!(
+            from != to,
+            "Cannot transfer admin permissions to the same account"
+        );
+        let (context, from_permissions) =
+            self.authorize_admin_for(role, from, &env::predecessor_account_id())?;
+        if !from_permissions.contains(role.admin_flag()) {
+            return Some(false);
+        }
+        if from_permissions.contains(AclPermissions::SUPER_ADMIN) {
+            Self::require_ok(Err(AclError::RedundantRevoke {
+                account_id: from.clone(),
+                role: format!("{:?}", role),
+            }));
+        }
+        let to_permissions = self.get_or_init_permissions(to);
+        if to_permissions.contains(role.admin_flag()) {
+            // Then the transfer only removes `from` from the admins.
+            self.assert_above_min_admins(role, from_permissions);
+        }
+        // Revoking first keeps the number of admins within a maximum.
+        self.revoke_admin_internal(
+            role,
+            from,
+            from_permissions,
+            AclEventId::AdminRevoked,
+            &context,
+        );
+        self.add_admin_internal(role, to, self.get_or_init_permissions(to), &context);
+        Some(true)
+    }
+
+    /// Revokes admin rights for `role` from the calling account. Returns
+    /// whether the caller was an admin for `role`.
+    ///
+    /// Fails if the caller is a super admin, since it would remain admin for
+    /// `role` via [`AclPermissions::SUPER_ADMIN`]. Such a caller must renounce
+    /// super admin first.
+    ///
+    /// Unless `force` is set, panics if the caller is the last admin for `role`
+    /// and there is no super admin, since nobody could administer `role`
+    /// afterwards.
+    pub fn renounce_admin(&mut self, role: R, force: bool) -> Result<bool, AclError> {
+        let predecessor = env::predecessor_account_id();
+        if self.is_super_admin(&predecessor) {
+            return Err(AclError::RedundantRenounce {
+                role: format!("{:?}", role),
+            });
+        }
+        let permissions = self.get_or_init_permissions(&predecessor);
+        self.assert_above_min_admins(role, permissions);
+        if !force && permissions.contains(role.admin_flag()) {
+            require!(
+                self.count_admins(role) > 1 || self.has_super_admin(),
+                format!(
+                    "Caller is the last admin for {:?}, pass force to renounce anyway",
+                    role
+                ),
+            );
+        }
+        Ok(self.revoke_admin_internal(
+            role,
+            &predecessor,
+            permissions,
+            AclEventId::AdminRenounced,
+            &AclEventContext::default(),
+        ))
+    }
+
+    /// Revokes admin rights for `role` from `account_id`, whose current
+    /// permissions are `permissions`. The event `id` is emitted with `context`
+    /// recorded in it.
+    fn revoke_admin_internal(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        mut permissions: AclPermissions,
+        id: AclEventId,
+        context: &AclEventContext,
+    ) -> bool {
+        let flag: AclPermissions = role.admin_flag();
+
+        let was_admin = permissions.contains(flag);
+        if was_admin {
+            let before = permissions;
+            permissions.remove(flag);
+            self.store_permissions(account_id, &permissions);
+            self.remove_bearer(flag, account_id);
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(&self.event_prefix, id, role, account_id)
+                        .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        was_admin
+    }
+
+    /// Grants super admin permissions to `account_id`, given that the
+    /// predecessor is a super admin. Returns `Some(bool)` indicating whether
+    /// `account_id` was newly added to the super admins.
+    ///
+    /// If the predecessor is not a super admin, permissions are not modified
+    /// and `None` is returned.
+    pub fn add_super_admin(&mut self, account_id: &AccountId) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let context = AclEventContext::authorized(AclAuthorizedVia::Super);
+        Some(self.add_super_admin_internal(account_id, &context))
+    }
+
+    /// Grants super admin permissions to `account_id`, __without__ checking
+    /// permissions of the predecessor.
+    ///
+    /// Returns whether `account_id` was newly added to the super admins.
+    pub fn add_super_admin_unchecked(&mut self, account_id: &AccountId) -> bool {
+        self.add_super_admin_internal(account_id, &AclEventContext::default())
+    }
+
+    /// Grants super admin permissions to `account_id`. The `context` is
+    /// recorded in the emitted event.
+    fn add_super_admin_internal(
+        &mut self,
+        account_id: &AccountId,
+        context: &AclEventContext,
+    ) -> bool {
+        let flag: AclPermissions = AclAdmin::Super.into();
+        let mut permissions = self.get_or_init_permissions(account_id);
+
+        let is_new_super_admin = !permissions.contains(flag);
+        if is_new_super_admin {
+            let before = permissions;
+            permissions.insert(flag);
+            self.store_permissions(account_id, &permissions);
+            self.add_bearer(flag, account_id);
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(
+                        &self.event_prefix,
+                        AclEventId::SuperAdminAdded,
+                        AclAdmin::Super,
+                        account_id,
+                    )
+                    .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        is_new_super_admin
+    }
+
+    /// Proposes `new_admin` as super admin, given that the predecessor is a
+    /// super admin. The proposal takes effect once `new_admin` accepts it via
+    /// [`Self::accept_super_admin`]. Returns `Some(bool)` indicating whether a
+    /// pending proposal was overwritten.
+    ///
+    /// If the predecessor is not a super admin, no proposal is made and `None`
+    /// is returned.
+    pub fn propose_super_admin(&mut self, new_admin: AccountId) -> Option<bool> {
+        Self::assert_grantable(&new_admin);
+        let proposer = env::predecessor_account_id();
+        if !self.is_super_admin(&proposer) {
+            return None;
+        }
+        let old = self.super_admin_proposal.replace(SuperAdminProposal {
+            proposer,
+            new_admin,
+        });
+        Some(old.is_some())
+    }
+
+    /// Makes the predecessor a super admin, given that it was proposed via
+    /// [`Self::propose_super_admin`]. Returns whether the predecessor was newly
+    /// added to the super admins.
+    ///
+    /// The proposer remains a super admin and may renounce afterwards. Panics
+    /// if the predecessor is not the proposed account.
+    pub fn accept_super_admin(&mut self) -> bool {
+        let predecessor = env::predecessor_account_id();
+        let is_proposed = matches!(
+            self.super_admin_proposal.as_ref(),
+            Some(proposal) if proposal.new_admin == predecessor
+        );
+        require!(is_proposed, "Caller is not the proposed super admin");
+        self.super_admin_proposal = None;
+        self.add_super_admin_unchecked(&predecessor)
+    }
+
+    /// Revokes super admin permissions from the calling account. Returns
+    /// whether the caller was a super admin.
+    pub fn renounce_super_admin(&mut self) -> bool {
+        self.revoke_super_admin_unchecked(&env::predecessor_account_id())
+    }
+
+    /// Revokes super admin permissions from `account_id`, given that the
+    /// predecessor is a super admin. Returns `Some(bool)` indicating whether
+    /// `account_id` was a super admin.
+    ///
+    /// Panics if `account_id` is the last super admin. If the predecessor is
+    /// not a super admin, permissions are not modified and `None` is returned.
+    pub fn revoke_super_admin(&mut self, account_id: &AccountId) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let context = AclEventContext::authorized(AclAuthorizedVia::Super);
+        Some(self.revoke_super_admin_internal(account_id, &context))
+    }
+
+    /// Revokes super admin permissions from `account_id` without checking any
+    /// permissions. Returns whether `account_id` was a super admin.
+    ///
+    /// Panics if `account_id` is the last super admin, since administration
+    /// of the contract would be locked permanently otherwise.
+    fn revoke_super_admin_unchecked(&mut self, account_id: &AccountId) -> bool {
+        self.revoke_super_admin_internal(account_id, &AclEventContext::default())
+    }
+
+    /// Revokes super admin permissions from `account_id`. The `context` is
+    /// recorded in the emitted event.
+    fn revoke_super_admin_internal(
+        &mut self,
+        account_id: &AccountId,
+        context: &AclEventContext,
+    ) -> bool {
+        let flag: AclPermissions = AclAdmin::Super.into();
+        let mut permissions = self.get_or_init_permissions(account_id);
+
+        let was_super_admin = permissions.contains(flag);
+        if was_super_admin {
+            require!(
+                self.count_bearers(flag) > 1,
+                "Cannot revoke the last super admin",
+            );
+            let before = permissions;
+            permissions.remove(flag);
+            self.store_permissions(account_id, &permissions);
+            self.remove_bearer(flag, account_id);
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(
+                        &self.event_prefix,
+                        AclEventId::SuperAdminRevoked,
+                        AclAdmin::Super,
+                        account_id,
+                    )
+                    .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        was_super_admin
+    }
+
+    /// Revokes all roles and admin permissions from `account_id` and removes
+    /// its entry, given that the predecessor is a super admin. Returns
+    /// `Some(bool)` indicating whether `account_id` had any permissions.
+    ///
+    /// Panics if `account_id` is the last super admin. If the predecessor is
+    /// not a super admin, permissions are not modified and `None` is returned.
+    pub fn revoke_all(&mut self, account_id: &AccountId) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let permissions = match self.permissions.get(account_id) {
+            Some(permissions) => permissions,
+            None => return Some(false),
+        };
+        if permissions.contains(AclPermissions::SUPER_ADMIN) {
+            require!(
+                self.count_bearers(AclPermissions::SUPER_ADMIN) > 1,
+                "Cannot revoke the last super admin",
+            );
+        }
+        for role in R::all_roles() {
+            self.assert_above_min_admins(role, permissions);
+        }
+
+        self.remove_permissions(account_id);
+        let revoked: Vec<AclPermissions> = self
+            .known_flags()
+            .into_iter()
+            .filter(|flag| permissions.contains(*flag))
+            .collect();
+        for flag in revoked.iter() {
+            self.remove_bearer(*flag, account_id);
+        }
+        if !revoked.is_empty() {
+            self.emit_event(
+                || {
+                    let names: Vec<String> =
+                        revoked.iter().map(|flag| self.flag_name(*flag)).collect();
+                    AclEvent::new_from_env(
+                        &self.event_prefix,
+                        AclEventId::AllRevoked,
+                        names,
+                        account_id,
+                    )
+                    .with_context(&AclEventContext::authorized(AclAuthorizedVia::Super))
+                },
+                permissions,
+                AclPermissions::empty(),
+            );
+        }
+
+        Some(!revoked.is_empty())
+    }
+
+    /// Removes the permissions of all accounts except the predecessor, given
+    /// that the predecessor is a super admin. The predecessor keeps only its
+    /// super admin flag, so the ACL remains administrable. Denials of roles in
+    /// denylist mode, delegations and onboarding records are removed, too,
+    /// including those of the predecessor. Meant for test networks and
+    /// redeployments.
+    ///
+    /// At most `limit` accounts and `limit` entries of each other collection
+    /// are cleared per call. `limit` defaults to and is capped at
+    /// [`ACL_MAX_BATCH_SIZE`]. Returns `Ok(Some(bool))` indicating whether
+    /// entries remain, in which case the method should be called again. A
+    /// single `Reset` event is emitted per call that modified state.
+    ///
+    /// Reset removes admins regardless of [`Self::set_min_admins`], so it
+    /// panics if a minimum number of admins is set for any role. Minimums must
+    /// be removed first. Maximums are not affected since reset only removes
+    /// admins.
+    ///
+    /// Requires enumeration of accounts, otherwise
+    /// [`AclError::EnumerationDisabled`] is returned. If the predecessor is not
+    /// a super admin, permissions are not modified and `Ok(None)` is returned.
+    pub fn reset(&mut self, limit: Option<usize>) -> Result<Option<bool>, AclError> {
+        let predecessor = env::predecessor_account_id();
+        if !self.is_super_admin(&predecessor) {
+            return Ok(None);
+        }
+        for role in R::all_roles() {
+            require!(
+                self.min_admins.get(&role).unwrap_or(0) == 0,
+                format!(
+                    "Remove the minimum number of admins of {:?} before resetting",
+                    role
+                ),
+            );
+        }
+        let limit = limit.unwrap_or(ACL_MAX_BATCH_SIZE).min(ACL_MAX_BATCH_SIZE);
+        let cleared: Vec<AccountId> = self
+            .accounts
+            .as_ref()
+            .ok_or(AclError::EnumerationDisabled)?
+            .iter()
+            .filter(|account_id| *account_id != predecessor)
+            .take(limit)
+            .collect();
+
+        for account_id in cleared.iter() {
+            self.clear_account(account_id, AclPermissions::empty());
+        }
+        let is_predecessor_cleared = self.clear_account(&predecessor, AclPermissions::SUPER_ADMIN);
+        let num_removed_entries = Self::drain(&mut self.role_denials, limit)
+            + Self::drain(&mut self.delegated_admins, limit)
+            + Self::drain(&mut self.onboarded, limit);
+
+        let num_cleared = cleared.len() as u64;
+        if num_cleared > 0 || is_predecessor_cleared || num_removed_entries > 0 {
+            let context = AclEventContext::authorized(AclAuthorizedVia::Super);
+            let contract_id = env::current_account_id();
+            self.emit_summary_event(|| {
+                AclEvent::new_from_env(
+                    &self.event_prefix,
+                    AclEventId::Reset,
+                    num_cleared,
+                    &contract_id,
+                )
+                .with_context(&context)
+            });
+        }
+        Ok(Some(
+            self.num_accounts > 1
+                || !self.role_denials.is_empty()
+                || !self.delegated_admins.is_empty()
+                || !self.onboarded.is_empty(),
+        ))
+    }
+
+    /// Removes up to `limit` elements from `set`. Returns the number of removed
+    /// elements.
+    fn drain<T: BorshSerialize + BorshDeserialize>(
+        set: &mut UnorderedSet<T>,
+        limit: usize,
+    ) -> usize {
+        let removed: Vec<T> = set.iter().take(limit).collect();
+        for element in removed.iter() {
+            set.remove(element);
+        }
+        removed.len()
+    }
+
+    /// Removes the permissions of `account_id` except for the flags in `keep`,
+    /// along with the schedules of its role grants. Returns whether
+    /// permissions were removed.
+    fn clear_account(&mut self, account_id: &AccountId, keep: AclPermissions) -> bool {
+        let permissions = self.get_or_init_permissions(account_id);
+        for flag in self.known_flags() {
+            if permissions.contains(flag) && !keep.contains(flag) {
+                self.remove_bearer(flag, account_id);
+            }
+        }
+        for role in R::all_roles() {
+            self.clear_role_schedule(account_id, role.into());
+        }
+        let kept = permissions & keep;
+        let is_changed = kept != permissions;
+        if is_changed {
+            self.store_permissions(account_id, &kept);
+        }
+        is_changed
+    }
+
+    /// Overwrites the permissions of accounts with the bitmasks in `entries`,
+    /// given that the predecessor is a super admin. Meant for seeding an ACL
+    /// from another one. Returns `Some(bool)` indicating whether any
+    /// permissions changed.
+    ///
+    /// Instead of an event per flag, a single `ImportCompleted` event is
+    /// emitted if any permissions changed. Schedules of imported role grants
+    /// are removed, making them permanent. An account whose schedule was
+    /// removed counts as changed even if its bitmask equals the imported one.
+    ///
+    /// Panics if more than [`ACL_MAX_BATCH_SIZE`] entries are passed, if a
+    /// bitmask contains flags which are neither defined in `AclPermissions` nor
+    /// belong to a registered role, or if no super admin would remain. Like
+    /// [`Self::grant_role`] and [`Self::add_admin`], it panics if an entry's
+    /// account must not be granted permissions, and entries are subject to
+    /// the limits set by [`Self::set_max_admins`] and [`Self::set_min_admins`],
+    /// which are checked entry by entry. If the predecessor is not a super
+    /// admin, permissions are not modified and `None` is returned.
+    pub fn import_permissions(&mut self, entries: Vec<(AccountId, u128)>) -> Option<bool> {
+        require!(
+            entries.len() <= ACL_MAX_BATCH_SIZE,
+            format!("At most {} accounts can be imported", ACL_MAX_BATCH_SIZE),
+        );
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+
+        let known_flags = self.known_flags();
+        let known_mask = known_flags
+            .iter()
+            .fold(AclPermissions::empty(), |mask, flag| mask | *flag);
+        let mut num_changed: u64 = 0;
+        for (account_id, bits) in entries.iter() {
+            Self::assert_grantable(account_id);
+            let imported =
+                AclPermissions::from_known_bits(*bits, known_mask).unwrap_or_else(|| {
+                    env::panic_str(&format!(
+                        "Permissions {:#x} of {} contain unknown flags",
+                        bits, account_id
+                    ))
+                });
+            let current = self.get_or_init_permissions(account_id);
+            // Dropping a schedule changes state even if the mask is unchanged,
+            // e.g. it activates a grant that was not yet effective.
+            let mut is_schedule_removed = false;
+            for role in R::all_roles() {
+                let flag: AclPermissions = role.into();
+                if imported.contains(flag) {
+                    is_schedule_removed |= self
+                        .role_schedules
+                        .remove(&(account_id.clone(), flag))
+                        .is_some();
+                }
+            }
+            if imported == current && !is_schedule_removed {
+                continue;
+            }
+
+            for role in R::all_roles() {
+                let admin_flag = role.admin_flag();
+                if imported.contains(admin_flag) && !current.contains(admin_flag) {
+                    self.assert_below_max_admins(role);
+                } else if !imported.contains(admin_flag) {
+                    self.assert_above_min_admins(role, current);
+                }
+            }
+            for flag in known_flags.iter().copied() {
+                if imported.contains(flag) && !current.contains(flag) {
+                    self.add_bearer(flag, account_id);
+                } else if !imported.contains(flag) && current.contains(flag) {
+                    self.remove_bearer(flag, account_id);
+                }
+            }
+            self.store_permissions(account_id, &imported);
+            num_changed += 1;
+        }
+        require!(self.has_super_admin(), "Cannot remove the last super admin");
+
+        if num_changed > 0 {
+            let context = AclEventContext::authorized(AclAuthorizedVia::Super);
+            let contract_id = env::current_account_id();
+            self.emit_summary_event(|| {
+                AclEvent::new_from_env(
+                    &self.event_prefix,
+                    AclEventId::ImportCompleted,
+                    num_changed,
+                    &contract_id,
+                )
+                .with_context(&context)
+            });
+        }
+        Some(num_changed > 0)
+    }
+
+    /// Returns whether at least one account is a super admin.
+    ///
+    /// The number of super admins is read from the length of the
+    /// [`AclPermissions::SUPER_ADMIN`] bearers set, which is maintained on
+    /// every add and revoke. Hence this is a single storage read, independent
+    /// of the number of accounts with permissions.
+    pub fn has_super_admin(&self) -> bool {
+        self.count_bearers(AclAdmin::Super.into()) > 0
+    }
+
+    /// Returns the raw bitmask of the permissions of `account_id`, which is zero
+    /// if there are no permissions stored for `account_id`.
+    pub fn get_permission_bits(&self, account_id: &AccountId) -> u128 {
+        self.get_or_init_permissions(account_id).bits()
+    }
+
+    /// Returns whether granting `role` to `account_id` would change state,
+    /// i.e. whether [`Self::grant_role`] would emit an event. This is the case
+    /// if `role` is not stored for `account_id` or its grant is scheduled and
+    /// not active. The authorization of the predecessor is not considered.
+    pub fn would_grant_change(&self, role: R, account_id: &AccountId) -> bool {
+        let flag: AclPermissions = role.into();
+        if !self.get_or_init_permissions(account_id).contains(flag) {
+            return true;
+        }
+        match self.role_schedules.get(&(account_id.clone(), flag)) {
+            Some(schedule) => !schedule.is_active_at(env::block_timestamp()),
+            None => false,
+        }
+    }
+
+    /// Returns whether revoking `role` from `account_id` would change state,
+    /// i.e. whether [`Self::revoke_role`] would emit an event. This is the
+    /// case if `role` is stored for `account_id` or, in denylist mode, if
+    /// `account_id` has not been denied `role` yet. The authorization of the
+    /// predecessor is not considered.
+    pub fn would_revoke_change(&self, role: R, account_id: &AccountId) -> bool {
+        let flag: AclPermissions = role.into();
+        if self.get_or_init_permissions(account_id).contains(flag) {
+            return true;
+        }
+        self.denylist_roles.contains(flag) && !self.is_role_denied(account_id, flag)
+    }
+
+    /// Returns the roles, admin roles and super admin status of `account_id`
+    /// in one call, e.g. for rendering the permissions of an account. Roles
+    /// are listed in the order of [`Self::role_display_order`], like in
+    /// [`Self::get_roles`].
+    pub fn diagnose(&self, account_id: &AccountId) -> AclDiagnostics<R> {
+        let permissions = self.get_or_init_permissions(account_id);
+        AclDiagnostics {
+            roles: self.get_roles(account_id),
+            admin_roles: self
+                .role_display_order()
+                .into_iter()
+                .filter(|role| permissions.contains(role.admin_flag()))
+                .collect(),
+            is_super_admin: permissions.is_super_admin_flag_set(),
+        }
+    }
+
+    /// Returns the permissions that checks evaluate for `account_id`. Unlike
+    /// [`Self::get_permission_bits`], these exclude role grants which are not
+    /// active and include the roles implied by granted roles as well as, for a
+    /// super admin, the admin flags of all roles.
+    pub fn effective_permissions(&self, account_id: &AccountId) -> AclPermissions {
+        self.get_effective_permissions(account_id)
+    }
+
+    /// Returns a map from the name of every defined flag to whether
+    /// `account_id` holds it.
+    pub fn get_flags(&self, account_id: &AccountId) -> BTreeMap<String, bool> {
+        let permissions = self.get_or_init_permissions(account_id);
+        AclPermissions::iter_defined()
+            .map(|flag| (flag.name(), permissions.contains(flag)))
+            .collect()
+    }
+
+    /// Returns whether `account_id` has been granted `role`.
+    pub fn has_role(&self, role: R, account_id: &AccountId) -> bool {
+        self.get_effective_permissions(account_id)
+            .contains(role.into())
+    }
+
+    /// Returns whether `account_id` has `role` or is a super admin.
+    ///
+    /// Super admins may grant themselves any role, but [`Self::has_role`] is
+    /// `false` for roles they have not been granted. This matches checks like
+    /// [`Self::check_role`], which let super admins pass.
+    pub fn has_role_or_super(&self, role: R, account_id: &AccountId) -> bool {
+        let permissions = self.get_effective_permissions(account_id);
+        permissions.intersects(role.into() | AclPermissions::SUPER_ADMIN)
+    }
+
+    /// Returns whether `account_id` has at least one of `roles`. Unlike
+    /// [`Self::check_any`], this does not panic. Returns `false` if `roles` is
+    /// empty.
+    pub fn has_any_role(&self, roles: &[R], account_id: &AccountId) -> bool {
+        self.get_effective_permissions(account_id)
+            .intersects(AclPermissions::from_roles(roles))
+    }
+
+    /// Returns whether `account_id` has all of `roles`. Unlike
+    /// [`Self::check_all`], this does not panic. Returns `true` if `roles` is
+    /// empty.
+    pub fn has_all_roles(&self, roles: &[R], account_id: &AccountId) -> bool {
+        self.get_effective_permissions(account_id)
+            .contains(AclPermissions::from_roles(roles))
+    }
+
+    /// Returns the roles granted to `account_id`, listed in the role display
+    /// order. Returns an empty vector if `account_id` has no permissions.
+    pub fn get_roles(&self, account_id: &AccountId) -> Vec<R> {
+        let permissions = self.get_active_permissions(account_id);
+        self.role_display_order()
+            .into_iter()
+            .filter(|role| permissions.contains((*role).into()))
+            .collect()
+    }
+
+    /// Returns the roles `account_id` is an admin for, in the order of
+    /// [`Self::role_display_order`]. For a super admin these are all roles,
+    /// irrespective of the admin flags it holds.
+    pub fn get_admin_roles(&self, account_id: &AccountId) -> Vec<R> {
+        let permissions = self.get_or_init_permissions(account_id);
+        self.role_display_order()
+            .into_iter()
+            .filter(|role| permissions.admin_authorization(*role).is_some())
+            .collect()
+    }
+
+    /// Grants `role` to `account_id`, given that `predecessor` is an admin or
+    /// a delegated admin for `role`, see [`Self::set_delegated_admin`].
+    /// Returns `Some(bool)` indicating wheter `role` was newly granted to
+    /// `account_id`. See [`Self::add_admin`] regarding `predecessor`.
+    ///
+    /// If `predecessor` is neither, `account_id` is not granted the role and
+    /// `None` is returned. The same holds if `account_id` cannot be granted
+    /// permissions, see [`Self::try_grant_role`].
+    pub fn grant_role(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Option<bool> {
+        self.try_grant_role(role, account_id, predecessor).ok()
+    }
+
+    /// Like [`Self::grant_role`], but returns an error instead of `None`:
+    /// [`AclError::NotAdmin`] if `predecessor` is neither an admin nor a
+    /// delegated admin for `role` and [`AclError::GrantToContract`] or
+    /// [`AclError::GrantToSystemAccount`] if `account_id` cannot be granted
+    /// permissions.
+    pub fn try_grant_role(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Result<bool, AclError> {
+        Self::check_grantable(account_id)?;
+        let (context, permissions) = self
+            .authorize_grantor_for(role, account_id, predecessor)
+            .ok_or_else(|| Self::not_admin_error(role, predecessor))?;
+        Ok(self.grant_role_internal(role, account_id, permissions, &context))
+    }
+
+    /// Like [`Self::grant_role`], but returns `Some` with the permissions
+    /// `account_id` had before the grant. Allows callers to tell what changed
+    /// or to restore the previous state.
+    pub fn grant_role_returning(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+    ) -> Option<AclPermissions> {
+        Self::assert_grantable(account_id);
+        let (context, permissions) =
+            self.authorize_grantor_for(role, account_id, &env::predecessor_account_id())?;
+        self.grant_role_internal(role, account_id, permissions, &context);
+        Some(permissions)
+    }
+
+    /// Grants `role` to each of `account_ids`, given that the predecessor is an
+    /// admin or a delegated admin for `role`. The admin check is done only once. Returns
+    /// `Some(Vec<bool>)` indicating for each account, in input order, whether
+    /// `role` was newly granted.
+    ///
+    /// Panics if more than [`ACL_MAX_BATCH_SIZE`] accounts are passed. If the
+    /// predecessor is not an admin for `role`, no account is granted the role
+    /// and `None` is returned.
+    pub fn grant_role_to_many(&mut self, role: R, account_ids: &[AccountId]) -> Option<Vec<bool>> {
+        account_ids.iter().for_each(Self::assert_grantable);
+        require!(
+            account_ids.len() <= ACL_MAX_BATCH_SIZE,
+            format!(
+                "At most {} accounts can be granted a role",
+                ACL_MAX_BATCH_SIZE
+            ),
+        );
+        let authorized_via = self.grantor_authorization(role, &env::predecessor_account_id())?;
+        let context = AclEventContext::authorized(authorized_via);
+        Some(
+            account_ids
+                .iter()
+                .map(|account_id| {
+                    self.grant_role_internal(
+                        role,
+                        account_id,
+                        self.get_or_init_permissions(account_id),
+                        &context,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Grants every flag in `mask` to `account_id`, given that the predecessor
+    /// is an admin for every role whose flag or admin flag is in `mask`, and a
+    /// super admin if `mask` contains [`AclPermissions::SUPER_ADMIN`]. Returns
+    /// `Some(bool)` indicating whether `account_id` gained new permissions.
+    /// The regular event is emitted per newly granted flag.
+    ///
+    /// Panics if `mask` contains flags which do not belong to a role. If the
+    /// predecessor lacks authorization for any flag, permissions are not
+    /// modified and `None` is returned.
+    pub fn grant_permissions(
+        &mut self,
+        account_id: &AccountId,
+        mask: AclPermissions,
+    ) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        let known_flags = R::all_roles()
+            .into_iter()
+            .fold(AclPermissions::SUPER_ADMIN, |flags, role| {
+                flags | role.into() | role.admin_flag()
+            });
+        require!(
+            known_flags.contains(mask),
+            format!(
+                "Permissions {:#x} contain flags of unknown roles",
+                mask.bits()
+            ),
+        );
+
+        let predecessor_permissions = self.get_or_init_permissions(&env::predecessor_account_id());
+        let mut grants = vec![];
+        for role in R::all_roles() {
+            if mask.intersects(role.into() | role.admin_flag()) {
+                let authorized_via = predecessor_permissions.admin_authorization(role)?;
+                grants.push((role, AclEventContext::authorized(authorized_via)));
+            }
+        }
+        let grants_super_admin = mask.contains(AclPermissions::SUPER_ADMIN);
+        if grants_super_admin && !predecessor_permissions.contains(AclPermissions::SUPER_ADMIN) {
+            return None;
+        }
+
+        let mut is_changed = false;
+        for (role, context) in grants.iter() {
+            if mask.contains((*role).into()) {
+                let permissions = self.get_or_init_permissions(account_id);
+                is_changed |= self.grant_role_internal(*role, account_id, permissions, context);
+            }
+            if mask.contains(role.admin_flag()) {
+                let permissions = self.get_or_init_permissions(account_id);
+                is_changed |= self.add_admin_internal(*role, account_id, permissions, context);
+            }
+        }
+        if grants_super_admin {
+            let context = AclEventContext::authorized(AclAuthorizedVia::Super);
+            is_changed |= self.add_super_admin_internal(account_id, &context);
+        }
+        Some(is_changed)
+    }
+
+    /// Like [`Self::grant_role`], but attaches `extra` as custom data to the
+    /// emitted event, e.g. a reason string or a request id.
+    pub fn grant_role_with_extra(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        extra: serde_json::Value,
+    ) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        let (context, permissions) =
+            self.authorize_grantor_for(role, account_id, &env::predecessor_account_id())?;
+        let context = context.with_extra(extra);
+        Some(self.grant_role_internal(role, account_id, permissions, &context))
+    }
+
+    /// Like [`Self::grant_role`], but records `memo` in the emitted event.
+    pub fn grant_role_with_memo(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        memo: String,
+    ) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        let (context, permissions) =
+            self.authorize_grantor_for(role, account_id, &env::predecessor_account_id())?;
+        let context = context.with_memo(memo);
+        Some(self.grant_role_internal(role, account_id, permissions, &context))
+    }
+
+    /// Grants `role` to `account_id` until the block timestamp reaches
+    /// `expires_at`, given that the predecessor is an admin for `role`. Returns
+    /// `Some(bool)` indicating whether `account_id` did not have an active
+    /// grant of `role` before.
+    ///
+    /// If the predecessor is not an admin for `role`, `account_id` is not
+    /// granted the role and `None` is returned.
+    pub fn grant_role_until(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        expires_at: u64,
+    ) -> Option<bool> {
+        let schedule = AclGrantSchedule {
+            effective_at: None,
+            expires_at: Some(expires_at.into()),
+        };
+        self.grant_role_scheduled(role, account_id, schedule)
+    }
+
+    /// Grants `role` to `account_id` such that the grant becomes active once
+    /// the block timestamp reaches `effective_at`, given that the predecessor
+    /// is an admin for `role`. Returns `Some(bool)` indicating whether
+    /// `account_id` did not have an active grant of `role` before.
+    ///
+    /// If the predecessor is not an admin for `role`, `account_id` is not
+    /// granted the role and `None` is returned.
+    pub fn grant_role_from(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        effective_at: u64,
+    ) -> Option<bool> {
+        let schedule = AclGrantSchedule {
+            effective_at: Some(effective_at.into()),
+            expires_at: None,
+        };
+        self.grant_role_scheduled(role, account_id, schedule)
+    }
+
+    /// Grants `role` to `account_id` with `schedule`, which replaces any
+    /// previous schedule of the grant.
+    fn grant_role_scheduled(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        schedule: AclGrantSchedule,
+    ) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        let (context, permissions) =
+            self.authorize_grantor_for(role, account_id, &env::predecessor_account_id())?;
+        let is_new_grantee = self.grant_role_internal(role, account_id, permissions, &context);
+        self.role_schedules
+            .insert(&(account_id.clone(), role.into()), &schedule);
+        Some(is_new_grantee)
+    }
+
+    /// Returns the schedule of the grant of `role` to `account_id`, or `None`
+    /// if the grant is permanent or absent.
+    pub fn role_schedule(&self, role: R, account_id: &AccountId) -> Option<AclGrantSchedule> {
+        self.role_schedules.get(&(account_id.clone(), role.into()))
+    }
+
+    /// Grants `role` to `account_id` __without__ checking any permissions.
+    /// Returns whether `role` was newly granted to `account_id`.
+    fn grant_role_unchecked(&mut self, role: R, account_id: &AccountId) -> bool {
+        self.grant_role_internal(
+            role,
+            account_id,
+            self.get_or_init_permissions(account_id),
+            &AclEventContext::default(),
+        )
+    }
+
+    /// Grants `role` to `account_id`, whose current permissions are
+    /// `permissions`. The `context` is recorded in the emitted event.
+    fn grant_role_internal(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        permissions: AclPermissions,
+        context: &AclEventContext,
+    ) -> bool {
+        self.grant_flag_internal(role.into(), role, account_id, permissions, context)
+    }
+
+    /// Inserts the role `flag` into the `permissions` of `account_id`. The
+    /// emitted event names the role as `role`, see
+    /// [`Self::add_admin_flag_internal`].
+    fn grant_flag_internal<T: Serialize>(
+        &mut self,
+        flag: AclPermissions,
+        role: T,
+        account_id: &AccountId,
+        mut permissions: AclPermissions,
+        context: &AclEventContext,
+    ) -> bool {
+        let was_inactive = self.clear_role_schedule(account_id, flag);
+        self.clear_role_denial(account_id, flag);
+        let is_new_grantee = !permissions.contains(flag) || was_inactive;
+        if is_new_grantee {
+            let before = permissions;
+            permissions.insert(flag);
+            self.store_permissions(account_id, &permissions);
+            self.add_bearer(flag, account_id);
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(
+                        &self.event_prefix,
+                        AclEventId::RoleGranted,
+                        role,
+                        account_id,
+                    )
+                    .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        is_new_grantee
+    }
+
+    /// Grants `role` and admin permissions for `role` to `account_id`, given
+    /// that the predecessor is an admin for `role`. Returns `Some(bool)`
+    /// indicating whether `account_id` gained new permissions.
+    ///
+    /// If the predecessor is not an admin for `role`, permissions are not
+    /// modified and `None` is returned.
+    pub fn grant_role_and_admin(&mut self, role: R, account_id: &AccountId) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        let authorized_via = self.admin_authorization(role, &env::predecessor_account_id())?;
+        let context = AclEventContext::authorized(authorized_via);
+        Some(self.grant_role_and_admin_internal(role, account_id, &context))
+    }
+
+    /// Grants `role` and admin permissions for `role` to `account_id`. Returns
+    /// whether `account_id` gained new permissions.
+    ///
+    /// By default `RoleGranted` and `AdminAdded` are emitted for the flags that
+    /// were newly set. If combined grant events are enabled, a single
+    /// `RoleAndAdminGranted` event is emitted instead.
+    fn grant_role_and_admin_internal(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        context: &AclEventContext,
+    ) -> bool {
+        if !self.combine_grant_events {
+            let is_new_grantee = self.grant_role_internal(
+                role,
+                account_id,
+                self.get_or_init_permissions(account_id),
+                context,
+            );
+            let is_new_admin = self.add_admin_internal(
+                role,
+                account_id,
+                self.get_or_init_permissions(account_id),
+                context,
+            );
+            return is_new_grantee || is_new_admin;
+        }
+
+        let role_flag: AclPermissions = role.into();
+        let admin_flag: AclPermissions = role.admin_flag();
+        let mut permissions = self.get_or_init_permissions(account_id);
+
+        let was_inactive = self.clear_role_schedule(account_id, role_flag);
+        self.clear_role_denial(account_id, role_flag);
+        let is_changed = !permissions.contains(role_flag | admin_flag) || was_inactive;
+        if is_changed {
+            if !permissions.contains(admin_flag) {
+                self.assert_below_max_admins(role);
+            }
+            let before = permissions;
+            permissions.insert(role_flag | admin_flag);
+            self.store_permissions(account_id, &permissions);
+            self.add_bearer(role_flag, account_id);
+            self.add_bearer(admin_flag, account_id);
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(
+                        &self.event_prefix,
+                        AclEventId::RoleAndAdminGranted,
+                        role,
+                        account_id,
+                    )
+                    .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        is_changed
+    }
+
+    /// Revoke `role` from `account_id`. If `predecessor` is an admin for
+    /// `role`, it returns `Some(bool)` indicating whether `account_id` was a
+    /// grantee of `role`. See [`Self::add_admin`] regarding `predecessor`.
+    ///
+    /// If `predecessor` is not an admin for `role`, it returns `None` and
+    /// permissions are not modified.
+    pub fn revoke_role(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Option<bool> {
+        self.try_revoke_role(role, account_id, predecessor).ok()
+    }
+
+    /// Like [`Self::revoke_role`], but returns [`AclError::NotAdmin`] instead
+    /// of `None` if `predecessor` is not an admin for `role`.
+    pub fn try_revoke_role(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        predecessor: &AccountId,
+    ) -> Result<bool, AclError> {
+        let (context, permissions) = self
+            .authorize_admin_for(role, account_id, predecessor)
+            .ok_or_else(|| Self::not_admin_error(role, predecessor))?;
+        Ok(self.revoke_role_internal(
+            role,
+            account_id,
+            permissions,
+            AclEventId::RoleRevoked,
+            &context,
+        ))
+    }
+
+    /// Like [`Self::revoke_role`], but records `memo` in the emitted event.
+    pub fn revoke_role_with_memo(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        memo: String,
+    ) -> Option<bool> {
+        let (context, permissions) =
+            self.authorize_admin_for(role, account_id, &env::predecessor_account_id())?;
+        let context = context.with_memo(memo);
+        Some(self.revoke_role_internal(
+            role,
+            account_id,
+            permissions,
+            AclEventId::RoleRevoked,
+            &context,
+        ))
+    }
+
+    /// Revokes `from` and grants `to` to `account_id` in one call, given that
+    /// the predecessor is an admin for both roles. Returns `Some(bool)`
+    /// indicating whether any permissions changed. Emits `RoleRevoked` and
+    /// then `RoleGranted` for the respective changes.
+    ///
+    /// Panics if `from` and `to` are the same role. If the predecessor is not
+    /// an admin for both roles, permissions are not modified and `None` is
+    /// returned.
+    pub fn swap_role(&mut self, account_id: &AccountId, from: R, to: R) -> Option<bool> {
+        Self::assert_grantable(account_id);
+        require!(from != to, "Cannot swap a role with itself");
+        let (from_context, permissions) =
+            self.authorize_admin_for(from, account_id, &env::predecessor_account_id())?;
+        let (to_context, _) =
+            self.authorize_admin_for(to, account_id, &env::predecessor_account_id())?;
+        let was_revoked = self.revoke_role_internal(
+            from,
+            account_id,
+            permissions,
+            AclEventId::RoleRevoked,
+            &from_context,
+        );
+        let is_granted = self.grant_role_internal(
+            to,
+            account_id,
+            self.get_or_init_permissions(account_id),
+            &to_context,
+        );
+        Some(was_revoked || is_granted)
+    }
+
+    /// Revokes `role` from each of `account_ids`, given that the predecessor is
+    /// an admin for `role`. The admin check is done only once. Returns
+    /// `Some(Vec<bool>)` indicating for each account, in input order, whether
+    /// it was a grantee of `role`. If an account is listed more than once, only
+    /// its first occurrence reports `true`.
+    ///
+    /// Panics if more than [`ACL_MAX_BATCH_SIZE`] accounts are passed. If the
+    /// predecessor is not an admin for `role`, permissions are not modified
+    /// and `None` is returned.
+    pub fn revoke_role_from_many(
+        &mut self,
+        role: R,
+        account_ids: &[AccountId],
+    ) -> Option<Vec<bool>> {
+        require!(
+            account_ids.len() <= ACL_MAX_BATCH_SIZE,
+            format!(
+                "At most {} accounts can be revoked a role",
+                ACL_MAX_BATCH_SIZE
+            ),
+        );
+        let authorized_via = self.admin_authorization(role, &env::predecessor_account_id())?;
+        let context = AclEventContext::authorized(authorized_via);
+        Some(
+            account_ids
+                .iter()
+                .map(|account_id| {
+                    self.revoke_role_internal(
+                        role,
+                        account_id,
+                        self.get_or_init_permissions(account_id),
+                        AclEventId::RoleRevoked,
+                        &context,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Revokes `role` from `account_id`, whose current permissions are
+    /// `permissions`. The event `id` is emitted with `context` recorded in it.
+    fn revoke_role_internal(
+        &mut self,
+        role: R,
+        account_id: &AccountId,
+        permissions: AclPermissions,
+        id: AclEventId,
+        context: &AclEventContext,
+    ) -> bool {
+        self.revoke_flag_internal(role.into(), role, account_id, permissions, id, context)
+    }
+
+    /// Removes the role `flag` from the `permissions` of `account_id`. The
+    /// emitted event names the role as `role`, see
+    /// [`Self::add_admin_flag_internal`].
+    fn revoke_flag_internal<T: Serialize>(
+        &mut self,
+        flag: AclPermissions,
+        role: T,
+        account_id: &AccountId,
+        mut permissions: AclPermissions,
+        id: AclEventId,
+        context: &AclEventContext,
+    ) -> bool {
+        let was_inactive = self.clear_role_schedule(account_id, flag);
+        let is_stored = permissions.contains(flag);
+        let is_new_denial = self.denylist_roles.contains(flag)
+            && !self.is_role_denied(account_id, flag)
+            && self.role_denials.insert(&(account_id.clone(), flag));
+        if is_stored || is_new_denial {
+            let before = permissions;
+            if is_stored {
+                permissions.remove(flag);
+                self.store_permissions(account_id, &permissions);
+                self.remove_bearer(flag, account_id);
+            }
+            self.emit_event(
+                || {
+                    AclEvent::new_from_env(&self.event_prefix, id, role, account_id)
+                        .with_context(context)
+                },
+                before,
+                permissions,
+            );
+        }
+
+        (is_stored && !was_inactive) || is_new_denial
+    }
+
+    /// Removes the denial of the role with `flag` for `account_id`, if the role
+    /// is in denylist mode.
+    fn clear_role_denial(&mut self, account_id: &AccountId, flag: AclPermissions) {
+        if !self.denylist_roles.contains(flag) {
+            return;
+        }
+        self.role_denials.remove(&(account_id.clone(), flag));
+    }
+
+    /// Returns whether `account_id` has been denied the role with `flag`.
+    fn is_role_denied(&self, account_id: &AccountId, flag: AclPermissions) -> bool {
+        self.role_denials.contains(&(account_id.clone(), flag))
+    }
+
+    /// Revokes `role` from the calling account. Returns whether the caller was
+    /// a grantee of `role`.
+    pub fn renounce_role(&mut self, role: R) -> bool {
+        let predecessor = env::predecessor_account_id();
+        let permissions = self.get_or_init_permissions(&predecessor);
+        self.revoke_role_internal(
+            role,
+            &predecessor,
+            permissions,
+            AclEventId::RoleRenounced,
+            &AclEventContext::default(),
+        )
+    }
+
+    /// Registers a role called `name` at runtime, given that the predecessor is
+    /// a super admin. Returns `Some(bool)` indicating whether the role was newly
+    /// registered.
+    ///
+    /// The role is allocated the next free pair of flags after the ones used
+    /// by static roles and previously registered roles. Panics if `name` is the name
+    /// of a static role or if the flags of `AclPermissions` are exhausted.
+    pub fn register_role(&mut self, name: String) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        require!(
+            R::all_roles()
+                .iter()
+                .all(|role| format!("{:?}", role) != name),
+            format!("Role {} is a static role", name),
+        );
+        if self.dynamic_roles.get(&name).is_some() {
+            return Some(false);
+        }
+        // Roles are never unregistered, so positions are allocated densely
+        // after the flags of static roles. A role's flag is at an odd position.
+        let static_flags = R::all_roles()
+            .into_iter()
+            .fold(AclPermissions::SUPER_ADMIN, |flags, role| {
+                flags | role.into() | role.admin_flag()
+            });
+        let first_free = (u128::BITS - static_flags.bits().leading_zeros()) as usize | 1;
+        let shift = first_free + self.dynamic_roles.len() as usize * 2;
+        require!(
+            shift < MAX_BITFLAG_SHIFT as usize,
+            "No flags left to register a role",
+        );
+        self.dynamic_roles.insert(&name, &(shift as u8));
+        Some(true)
+    }
+
+    /// Returns the flag of the role registered as `name`.
+    fn dynamic_role_flag(&self, name: &str) -> Result<AclPermissions, AclError> {
+        match self.dynamic_roles.get(&name.to_string()) {
+            Some(shift) => AclPermissions::try_from_shift(shift),
+            None => Err(AclError::UnknownRole {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// Returns the flag of the admin of the role registered at runtime whose
+    /// flag is `flag`. It is at the next higher position.
+    fn dynamic_admin_flag(flag: AclPermissions) -> Result<AclPermissions, AclError> {
+        AclPermissions::try_from_shift(flag.bits().trailing_zeros() as u8 + 1)
+    }
+
+    /// Returns how the predecessor is authorized as an admin for the role
+    /// registered at runtime whose admin flag is `admin_flag`, or `None` if it
+    /// is not an admin for that role.
+    fn dynamic_admin_authorization(&self, admin_flag: AclPermissions) -> Option<AclAuthorizedVia> {
+        let predecessor_permissions = self.get_or_init_permissions(&env::predecessor_account_id());
+        if predecessor_permissions.contains(AclPermissions::SUPER_ADMIN) {
+            Some(AclAuthorizedVia::Super)
+        } else if predecessor_permissions.contains(admin_flag) {
+            Some(AclAuthorizedVia::RoleAdmin)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the flags of all roles registered at runtime and of their
+    /// admins.
+    fn dynamic_flags(&self) -> AclPermissions {
+        self.dynamic_roles
+            .values()
+            .fold(AclPermissions::empty(), |flags, shift| {
+                flags
+                    | AclPermissions::from_bits_retained(1u128 << shift)
+                    | AclPermissions::from_bits_retained(1u128 << (shift + 1))
+            })
+    }
+
+    /// Returns every flag an account may hold, in ascending bit order: the
+    /// flags defined in `AclPermissions` followed by those of roles registered
+    /// at runtime and of their admins.
+    fn known_flags(&self) -> Vec<AclPermissions> {
+        let dynamic_flags = self.dynamic_flags();
+        AclPermissions::iter_defined()
+            .chain(
+                (0..=MAX_BITFLAG_SHIFT)
+                    .map(|shift| AclPermissions::from_bits_retained(1u128 << shift))
+                    .filter(|flag| dynamic_flags.contains(*flag)),
+            )
+            .collect()
+    }
+
+    /// Returns the name of `flag` as listed in events. Flags of roles
+    /// registered at runtime are named like the role, the flags of their
+    /// admins get an `_ADMIN` suffix.
+    fn flag_name(&self, flag: AclPermissions) -> String {
+        if AclPermissions::from_bits(flag.bits()).is_none() {
+            for (name, shift) in self.dynamic_roles.iter() {
+                if flag.bits() == 1u128 << shift {
+                    return name;
+                }
+                if flag.bits() == 1u128 << (shift + 1) {
+                    return format!("{}_ADMIN", name);
+                }
+            }
+        }
+        flag.name()
+    }
+
+    /// Returns whether `account_id` has the role registered as `name`.
+    pub fn has_role_by_name(&self, name: &str, account_id: &AccountId) -> Result<bool, AclError> {
+        let flag = self.dynamic_role_flag(name)?;
+        Ok(self.get_effective_permissions(account_id).contains(flag))
+    }
+
+    /// Grants the role registered as `name` to `account_id`, given that the
+    /// predecessor is a super admin or an admin for that role. Returns
+    /// `Ok(Some(bool))` indicating whether the role was newly granted.
+    ///
+    /// If the predecessor is not an admin for the role, `account_id` is not
+    /// granted the role and `Ok(None)` is returned.
+    pub fn grant_role_by_name(
+        &mut self,
+        name: &str,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        Self::assert_grantable(account_id);
+        let flag = self.dynamic_role_flag(name)?;
+        let admin_flag = Self::dynamic_admin_flag(flag)?;
+        let authorized_via = match self.dynamic_admin_authorization(admin_flag) {
+            Some(authorized_via) => authorized_via,
+            None => return Ok(None),
+        };
+        Ok(Some(self.grant_flag_internal(
+            flag,
+            name.to_string(),
+            account_id,
+            self.get_or_init_permissions(account_id),
+            &AclEventContext::authorized(authorized_via),
+        )))
+    }
+
+    /// Revokes the role registered as `name` from `account_id`, given that the
+    /// predecessor is a super admin or an admin for that role. Returns
+    /// `Ok(Some(bool))` indicating whether `account_id` had the role.
+    ///
+    /// If the predecessor is not an admin for the role, permissions are not
+    /// modified and `Ok(None)` is returned.
+    pub fn revoke_role_by_name(
+        &mut self,
+        name: &str,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        let flag = self.dynamic_role_flag(name)?;
+        let admin_flag = Self::dynamic_admin_flag(flag)?;
+        let authorized_via = match self.dynamic_admin_authorization(admin_flag) {
+            Some(authorized_via) => authorized_via,
+            None => return Ok(None),
+        };
+        Ok(Some(self.revoke_flag_internal(
+            flag,
+            name.to_string(),
+            account_id,
+            self.get_or_init_permissions(account_id),
+            AclEventId::RoleRevoked,
+            &AclEventContext::authorized(authorized_via),
+        )))
+    }
+
+    /// Adds `account_id` to the admins of the role registered as `name`, given
+    /// that the predecessor is a super admin or an admin for that role. Returns
+    /// `Ok(Some(bool))` indicating whether `account_id` has gained new admin
+    /// permissions.
+    ///
+    /// If the predecessor is not an admin for the role, permissions are not
+    /// modified and `Ok(None)` is returned.
+    pub fn add_admin_by_name(
+        &mut self,
+        name: &str,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        Self::assert_grantable(account_id);
+        let admin_flag = Self::dynamic_admin_flag(self.dynamic_role_flag(name)?)?;
+        let authorized_via = match self.dynamic_admin_authorization(admin_flag) {
+            Some(authorized_via) => authorized_via,
+            None => return Ok(None),
+        };
+        Ok(Some(self.add_admin_flag_internal(
+            admin_flag,
+            name.to_string(),
+            account_id,
+            self.get_or_init_permissions(account_id),
+            &AclEventContext::authorized(authorized_via),
+        )))
+    }
+
+    /// Defines a group called `name` which consists of `roles`, given that the
+    /// predecessor is a super admin. Returns `Some(bool)` indicating whether
+    /// the definition of the group changed. An existing group called `name` is
+    /// redefined.
+    ///
+    /// Panics if `roles` is empty.
+    pub fn define_group(&mut self, name: String, roles: &[R]) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        require!(!roles.is_empty(), "A group must contain at least one role");
+        let flags = AclPermissions::from_roles(roles);
+        Some(self.role_groups.insert(&name, &flags) != Some(flags))
+    }
+
+    /// Returns the roles in the group called `name`.
+    pub fn group_roles(&self, name: &str) -> Result<Vec<R>, AclError> {
+        let flags =
+            self.role_groups
+                .get(&name.to_string())
+                .ok_or_else(|| AclError::UnknownGroup {
+                    name: name.to_string(),
+                })?;
+        Ok(flags.roles())
+    }
+
+    /// Returns the roles in the group called `name` along with an event
+    /// context for each of them, or `Ok(None)` if the predecessor is not an
+    /// admin for every role in the group.
+    fn authorize_admin_for_group(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<(R, AclEventContext)>>, AclError> {
+        let roles = self.group_roles(name)?;
+        let predecessor_permissions = self.get_or_init_permissions(&env::predecessor_account_id());
+        Ok(roles
+            .into_iter()
+            .map(|role| {
+                let authorized_via = predecessor_permissions.admin_authorization(role)?;
+                Some((role, AclEventContext::authorized(authorized_via)))
+            })
+            .collect())
+    }
+
+    /// Grants every role in the group called `name` to `account_id`, given
+    /// that the predecessor is an admin for each of them. Returns
+    /// `Ok(Some(bool))` indicating whether any role was newly granted. An event
+    /// is emitted per newly granted role.
+    ///
+    /// If the predecessor is not an admin for every role in the group, no role
+    /// is granted and `Ok(None)` is returned.
+    pub fn grant_group(
+        &mut self,
+        name: &str,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        Self::assert_grantable(account_id);
+        let authorized_roles = match self.authorize_admin_for_group(name)? {
+            Some(authorized_roles) => authorized_roles,
+            None => return Ok(None),
+        };
+        let mut granted_any = false;
+        for (role, context) in authorized_roles.iter() {
+            let permissions = self.get_or_init_permissions(account_id);
+            granted_any |= self.grant_role_internal(*role, account_id, permissions, context);
+        }
+        Ok(Some(granted_any))
+    }
+
+    /// Revokes every role in the group called `name` from `account_id`, given
+    /// that the predecessor is an admin for each of them. Returns
+    /// `Ok(Some(bool))` indicating whether `account_id` was a grantee of any
+    /// role in the group. An event is emitted per revoked role.
+    ///
+    /// If the predecessor is not an admin for every role in the group, no role
+    /// is revoked and `Ok(None)` is returned.
+    pub fn revoke_group(
+        &mut self,
+        name: &str,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        let authorized_roles = match self.authorize_admin_for_group(name)? {
+            Some(authorized_roles) => authorized_roles,
+            None => return Ok(None),
+        };
+        let mut revoked_any = false;
+        for (role, context) in authorized_roles.iter() {
+            let permissions = self.get_or_init_permissions(account_id);
+            revoked_any |= self.revoke_role_internal(
+                *role,
+                account_id,
+                permissions,
+                AclEventId::RoleRevoked,
+                context,
+            );
+        }
+        Ok(Some(revoked_any))
+    }
+
+    /// Sets the roles implied by `role`, given that the predecessor is a super
+    /// admin. Accounts granted `role` then pass checks for the `implied` roles
+    /// and for the roles these imply in turn. Passing no roles removes the
+    /// implications of `role`. Returns `Some(bool)` indicating whether the
+    /// implications changed.
+    ///
+    /// Implications only affect checks and queries like [`Self::has_role`].
+    /// They are not stored as grants, hence implied roles are neither listed by
+    /// [`Self::get_roles`] nor enumerated as grantees.
+    ///
+    /// Panics if `role` would imply itself, directly or transitively. If the
+    /// predecessor is not a super admin, implications are not modified and
+    /// `None` is returned.
+    pub fn set_role_implications(&mut self, role: R, implied: &[R]) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let flag: AclPermissions = role.into();
+        let implied = AclPermissions::from_roles(implied);
+        require!(
+            !self.expand_implications(implied).contains(flag),
+            format!("Implications of {:?} would form a cycle", role),
+        );
+        let previous = if implied.is_empty() {
+            self.role_implications.remove(&flag)
+        } else {
+            self.role_implications.insert(&flag, &implied)
+        };
+        Some(previous.unwrap_or_else(AclPermissions::empty) != implied)
+    }
+
+    /// Sets the mode of `role`, given that the predecessor is a super admin.
+    /// Returns `Some(bool)` indicating whether the mode changed.
+    ///
+    /// In [`AclRoleMode::Denylist`] mode every account has `role` unless it has
+    /// been denied the role, including accounts without stored permissions.
+    /// Revoking or renouncing the role denies it, granting it lifts the denial.
+    /// Denials are kept when switching back to allowlist mode, where they have
+    /// no effect, and apply again when switching to denylist mode. Mutations
+    /// of stored permissions like [`Self::revoke_all`] and
+    /// [`Self::import_permissions`] do not affect denials.
+    ///
+    /// Accounts that hold `role` by the denylist are not enumerated as
+    /// grantees. If the predecessor is not a super admin, the mode is not
+    /// modified and `None` is returned.
+    pub fn set_role_mode(&mut self, role: R, mode: AclRoleMode) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let flag: AclPermissions = role.into();
+        let is_changed = self.role_mode(role) != mode;
+        match mode {
+            AclRoleMode::Allowlist => self.denylist_roles.remove(flag),
+            AclRoleMode::Denylist => self.denylist_roles.insert(flag),
+        }
+        Some(is_changed)
+    }
+
+    /// Returns the mode of `role`.
+    pub fn role_mode(&self, role: R) -> AclRoleMode {
+        if self.denylist_roles.contains(role.into()) {
+            AclRoleMode::Denylist
+        } else {
+            AclRoleMode::Allowlist
+        }
+    }
+
+    /// Returns the roles directly implied by `role`.
+    pub fn role_implications(&self, role: R) -> Vec<R> {
+        match self.role_implications.get(&role.into()) {
+            Some(implied) => implied.roles(),
+            None => vec![],
+        }
+    }
+
+    /// Sets the requirement guarding `method`, given that the predecessor is a
+    /// super admin. Returns `Some(bool)` indicating whether the requirement
+    /// changed.
+    ///
+    /// If the predecessor is not a super admin, the requirement is not
+    /// modified and `None` is returned.
+    pub fn set_method_requirement(
+        &mut self,
+        method: String,
+        requirement: AclRequirement,
+    ) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        Some(self.set_method_requirement_unchecked(&method, requirement))
+    }
+
+    /// Sets the requirement guarding `method` __without__ checking permissions
+    /// of the predecessor. Returns whether the requirement changed.
+    ///
+    /// Panics if `requirement` contains no permissions, since an empty
+    /// [`AclRequirement::All`] would let anyone pass.
+    pub fn set_method_requirement_unchecked(
+        &mut self,
+        method: &str,
+        requirement: AclRequirement,
+    ) -> bool {
+        require!(
+            !requirement.target().is_empty(),
+            "Requirement must contain at least one permission",
+        );
+        let old = self
+            .method_requirements
+            .insert(&method.to_string(), &requirement);
+        old != Some(requirement)
+    }
+
+    /// Returns the requirement guarding `method`, or `None` if `method` is not
+    /// guarded.
+    pub fn method_requirement(&self, method: &str) -> Option<AclRequirement> {
+        self.method_requirements.get(&method.to_string())
+    }
+
+    /// Panics if `account_id` does not satisfy the requirement currently
+    /// stored for `method`. Methods without a stored requirement are rejected,
+    /// so a missing requirement does not open up a method.
+    pub fn check_method(&self, method: &str, account_id: &AccountId) {
+        match self.method_requirement(method) {
+            Some(AclRequirement::Any(target)) => self.check_any(target, account_id),
+            Some(AclRequirement::All(target)) => self.check_all(target, account_id),
+            None => AclError::UnguardedMethod {
+                method: method.to_string(),
+            }
+            .panic(),
+        }
+    }
+
+    /// Panics unless `account_id` has `role` or is a super admin. Like
+    /// [`Self::is_admin`], super admins pass for every role. Holding only the
+    /// admin flag of `role` does not suffice.
+    pub fn check_role(&self, role: R, account_id: &AccountId) {
+        if !self.is_super_admin(account_id) {
+            self.check_any(role.into(), account_id);
+        }
+    }
+
+    /// Panics unless `account_id` has `role` or is an admin for `role`,
+    /// including super admins.
+    pub fn check_role_or_admin(&self, role: R, account_id: &AccountId) {
+        let target = role.into() | role.admin_flag() | AclPermissions::SUPER_ADMIN;
+        self.check_any(target, account_id);
+    }
+
+    /// Panics unless the predecessor is an admin for `role`, which includes
+    /// super admins. The message names the role by its label, if one is set.
+    pub fn assert_admin(&self, role: R) {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            self.is_admin(role, &predecessor),
+            format!(
+                "Account {} must be an admin for role {}",
+                predecessor,
+                self.role_display_name(role)
+            )
+        );
+    }
+
+    /// Panics unless the predecessor has been granted `role`. Unlike
+    /// [`Self::require_any_role`], super admins do not pass unless they have
+    /// `role`. The message names the role by its label, if one is set.
+    pub fn assert_grantee(&self, role: R) {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            self.has_role(role, &predecessor),
+            format!(
+                "Account {} must have role {}",
+                predecessor,
+                self.role_display_name(role)
+            )
+        );
+    }
+
+    /// Returns the label of `role` followed by its name, e.g. `Moderator (L1)`,
+    /// or only its name if no label is set.
+    fn role_display_name(&self, role: R) -> String {
+        match self.role_label(role) {
+            Some(label) => format!("{} ({:?})", label, role),
+            None => format!("{:?}", role),
+        }
+    }
+
+    /// Panics unless the predecessor has at least one of `roles` or is a super
+    /// admin. Meant to guard a method in a single line, e.g.
+    /// `self.acl.require_any_role(&[Role::L2])`.
+    pub fn require_any_role(&self, roles: &[R]) {
+        let predecessor = env::predecessor_account_id();
+        if !self.is_super_admin(&predecessor) {
+            self.check_any(AclPermissions::from_roles(roles), &predecessor);
+        }
+    }
+
+    /// Panics unless the predecessor has all of `roles` or is a super admin.
+    pub fn require_all_roles(&self, roles: &[R]) {
+        let predecessor = env::predecessor_account_id();
+        if !self.is_super_admin(&predecessor) {
+            self.check_all(AclPermissions::from_roles(roles), &predecessor);
+        }
+    }
+
+    /// Panics if `account_id` does not have at least one of the permissions
+    /// specified in `target`.
+    pub fn check_any(&self, target: AclPermissions, account_id: &AccountId) {
+        if let Err(err) = self.try_check_any(target, account_id) {
+            err.panic()
+        }
+    }
+
+    /// Returns an error if `account_id` does not have at least one of the
+    /// permissions specified in `target`.
+    pub fn try_check_any(
+        &self,
+        target: AclPermissions,
+        account_id: &AccountId,
+    ) -> Result<(), AclError> {
+        let permissions = self.get_effective_permissions(account_id);
+        if !permissions.intersects(target) {
+            return Err(AclError::MissingAnyPermission {
+                account_id: account_id.clone(),
+                required: target,
+            });
+        }
+        Ok(())
+    }
+
+    /// Panics if `account_id` does not have all of the permissions specified in
+    /// `target`.
+    pub fn check_all(&self, target: AclPermissions, account_id: &AccountId) {
+        if let Err(err) = self.try_check_all(target, account_id) {
+            err.panic()
+        }
+    }
+
+    /// Returns an error if `account_id` does not have all of the permissions
+    /// specified in `target`.
+    pub fn try_check_all(
+        &self,
+        target: AclPermissions,
+        account_id: &AccountId,
+    ) -> Result<(), AclError> {
+        let permissions = self.get_effective_permissions(account_id);
+        if !permissions.contains(target) {
+            return Err(AclError::MissingAllPermissions {
+                account_id: account_id.clone(),
+                missing: target - permissions,
+                required: target,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns whether `account_id` has at least one of the permissions in
+    /// `target`. Evaluates exactly like [`Self::check_any`], but returns
+    /// `false` instead of panicking.
+    pub fn is_authorized_any(&self, target: AclPermissions, account_id: &AccountId) -> bool {
+        self.try_check_any(target, account_id).is_ok()
+    }
+
+    /// Returns whether `account_id` has all of the permissions in `target`.
+    /// Evaluates exactly like [`Self::check_all`], but returns `false` instead
+    /// of panicking.
+    pub fn is_authorized_all(&self, target: AclPermissions, account_id: &AccountId) -> bool {
+        self.try_check_all(target, account_id).is_ok()
+    }
+
+    /// Returns for each of `account_ids`, in input order, whether it satisfies
+    /// `requirement`. Unlike [`Self::check_any`] and [`Self::check_all`], this
+    /// does not panic on missing permissions.
+    ///
+    /// Panics if more than [`ACL_MAX_BATCH_SIZE`] accounts are passed.
+    pub fn check_batch(&self, requirement: AclRequirement, account_ids: &[AccountId]) -> Vec<bool> {
+        require!(
+            account_ids.len() <= ACL_MAX_BATCH_SIZE,
+            format!("At most {} accounts can be checked", ACL_MAX_BATCH_SIZE),
+        );
+        account_ids
+            .iter()
+            .map(|account_id| {
+                requirement.is_satisfied_by(self.get_effective_permissions(account_id))
+            })
+            .collect()
+    }
+
+    /// Adds `account_id` to the set of `permission` bearers.
+    fn add_bearer(&mut self, permission: AclPermissions, account_id: &AccountId) {
+        let mut set = match self.bearers.get(&permission) {
+            Some(set) => set,
+            None => self.new_bearers_set(permission),
+        };
+        if set.insert(account_id) {
+            self.bearers.insert(&permission, &set);
+        }
+    }
+
+    /// Removes `account_id` from the set of `permission` bearers.
+    fn remove_bearer(&mut self, permission: AclPermissions, account_id: &AccountId) {
+        let mut set = match self.bearers.get(&permission) {
+            Some(set) => set,
+            None => return, // nothing to do
+        };
+        if set.remove(account_id) {
+            self.bearers.insert(&permission, &set);
+        }
+    }
+
+    /// Enables or disables private enumeration, given that the predecessor is
+    /// a super admin. Returns `Some(bool)` indicating whether the setting
+    /// changed.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_private_enumeration(&mut self, enabled: bool) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let is_changed = self.private_enumeration != enabled;
+        self.private_enumeration = enabled;
+        Some(is_changed)
+    }
+
+    /// Sets the order in which views list roles, given that the predecessor is
+    /// a super admin. Passing `None` restores the default order by
+    /// discriminant. Returns `Some(bool)` indicating whether the setting
+    /// changed.
+    ///
+    /// Panics if `order` does not contain every role exactly once. If the
+    /// predecessor is not a super admin, the setting is not modified and
+    /// `None` is returned.
+    pub fn set_role_display_order(&mut self, order: Option<Vec<R>>) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        if let Some(order) = order.as_ref() {
+            let roles = R::all_roles();
+            require!(
+                order.len() == roles.len() && roles.iter().all(|role| order.contains(role)),
+                "Role display order must contain every role exactly once",
+            );
+        }
+        let is_changed = self.role_display_order != order;
+        self.role_display_order = order;
+        Some(is_changed)
+    }
+
+    /// Sets the role which is granted to accounts on their first call of the
+    /// entry method, given that the predecessor is a super admin. Passing
+    /// `None` disables auto granting. Returns `Some(bool)` indicating whether
+    /// the setting changed.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_auto_grant_role(&mut self, role: Option<R>) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let is_changed = self.auto_grant_role != role;
+        self.auto_grant_role = role;
+        Some(is_changed)
+    }
+
+    /// Grants the auto grant role to `account_id` unless it has been onboarded
+    /// before. Returns whether the role was granted.
+    ///
+    /// Accounts are only recorded as onboarded while an auto grant role is
+    /// set. An account that renounced the role is not granted it again, nor is
+    /// an account that was denied the role in [`AclRoleMode::Denylist`] mode.
+    ///
+    /// Like other grants, it panics if `account_id` must not be granted
+    /// permissions, e.g. if it is the contract itself.
+    pub fn onboard(&mut self, account_id: &AccountId) -> bool {
+        let role = match self.auto_grant_role {
+            Some(role) => role,
+            None => return false,
+        };
+        Self::assert_grantable(account_id);
+        if self.is_role_denied(account_id, role.into()) || !self.onboarded.insert(account_id) {
+            return false;
+        }
+        self.grant_role_unchecked(role, account_id)
+    }
+
+    /// Sets the label of `role`, e.g. `Moderator`, given that the predecessor
+    /// is a super admin. An existing label is overwritten. Returns
+    /// `Some(bool)` indicating whether the label changed.
+    ///
+    /// If the predecessor is not a super admin, the label is not modified and
+    /// `None` is returned.
+    pub fn set_role_label(&mut self, role: R, label: String) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        Some(self.role_labels.insert(&role, &label).as_ref() != Some(&label))
+    }
+
+    /// Returns the label of `role`, if one is set.
+    pub fn role_label(&self, role: R) -> Option<String> {
+        self.role_labels.get(&role)
+    }
+
+    /// Returns all roles in the order they should be listed by views.
+    pub fn role_display_order(&self) -> Vec<R> {
+        match self.role_display_order.as_ref() {
+            Some(order) => order.clone(),
+            None => R::all_roles().to_vec(),
+        }
+    }
+
+    /// Enables or disables including permission bitmasks in events, given that
+    /// the predecessor is a super admin. Returns `Some(bool)` indicating
+    /// whether the setting changed.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_event_masks(&mut self, enabled: bool) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let is_changed = self.event_masks != enabled;
+        self.event_masks = enabled;
+        Some(is_changed)
+    }
+
+    /// Enables or disables combined events for granting a role together with
+    /// its admin flag, given that the predecessor is a super admin. Returns
+    /// `Some(bool)` indicating whether the setting changed.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_combine_grant_events(&mut self, enabled: bool) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let is_changed = self.combine_grant_events != enabled;
+        self.combine_grant_events = enabled;
+        Some(is_changed)
+    }
+
+    /// Sets the prefix of emitted event names, given that the predecessor is a
+    /// super admin. This allows namespacing ACL events of a contract that
+    /// integrates multiple subsystems, e.g. `counter_acl_role_granted`.
+    /// Returns `Some(bool)` indicating whether the setting changed.
+    ///
+    /// If the predecessor is not a super admin, the setting is not modified
+    /// and `None` is returned.
+    pub fn set_event_prefix(&mut self, prefix: String) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        let is_changed = self.event_prefix != prefix;
+        self.event_prefix = prefix;
+        Some(is_changed)
+    }
+
+    /// Returns the history of recently emitted events, see
+    /// [`Self::set_event_history_capacity`].
+    #[cfg(feature = "event-history")]
+    fn event_history(&self) -> AclEventHistory {
+        AclEventHistory::new(&self.storage_prefix)
+    }
+
+    /// Sets how many of the most recently emitted events are kept in state,
+    /// given that the predecessor is a super admin. Once the history is full,
+    /// the oldest event is dropped for each new one. A capacity of zero, which
+    /// is the default, disables the history. Returns `Some(bool)` indicating
+    /// whether the capacity changed.
+    ///
+    /// Changing the capacity clears the history. Panics if `capacity` exceeds
+    /// [`ACL_MAX_BATCH_SIZE`]. If the predecessor is not a super admin, the
+    /// setting is not modified and `None` is returned.
+    #[cfg(feature = "event-history")]
+    pub fn set_event_history_capacity(&mut self, capacity: u64) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        require!(
+            capacity <= ACL_MAX_BATCH_SIZE as u64,
+            format!(
+                "Event history capacity must not exceed {}",
+                ACL_MAX_BATCH_SIZE
+            ),
+        );
+        let history = self.event_history();
+        if capacity == history.capacity() {
+            return Some(false);
+        }
+        history.set_capacity(capacity);
+        Some(true)
+    }
+
+    /// Returns up to `limit` events from the history, oldest first, skipping
+    /// the `skip` oldest ones.
+    #[cfg(feature = "event-history")]
+    pub fn recent_events(&self, skip: usize, limit: usize) -> Vec<StoredAclEvent> {
+        self.event_history().get(skip, limit)
+    }
+
+    /// Sets the version of emitted events, given that the predecessor is a
+    /// super admin. Contracts which extend the schema of events should bump
+    /// it, since consumers key off the version. Passing `None` restores the
+    /// default version. Returns `Some(bool)` indicating whether the setting
+    /// changed.
+    ///
+    /// Panics if `version` is not of the form `MAJOR.MINOR.PATCH`, e.g.
+    /// `1.1.0`. If the predecessor is not a super admin, the setting is not
+    /// modified and `None` is returned.
+    pub fn set_event_version(&mut self, version: Option<String>) -> Option<bool> {
+        if !self.is_super_admin(&env::predecessor_account_id()) {
+            return None;
+        }
+        if let Some(version) = version.as_ref() {
+            require!(
+                is_semver_core(version),
+                format!(
+                    "Event version {} is not of the form MAJOR.MINOR.PATCH",
+                    version
+                ),
+            );
+        }
+        let is_changed = self.event_version != version;
+        self.event_version = version;
+        Some(is_changed)
+    }
+
+    /// Returns the version of emitted events.
+    pub fn event_version(&self) -> &str {
+        self.event_version.as_deref().unwrap_or(EVENT_VERSION)
+    }
+
+    /// Emits the event built by `event` for a mutation that changed an
+    /// account's permissions from `before` to `after`. The masks are attached
+    /// if enabled.
+    ///
+    /// Callers emit an event if and only if the mutation changed stored
+    /// state, i.e. the permissions of an account or the schedule or denial of
+    /// one of its roles. Repeating a mutation therefore emits no further
+    /// events.
+    ///
+    /// Without feature `events`, nothing is emitted and `event` is not called,
+    /// so no event is constructed.
+    #[cfg(feature = "events")]
+    fn emit_event<'a, T, F>(&'a self, event: F, before: AclPermissions, after: AclPermissions)
+    where
+        T: Serialize,
+        F: FnOnce() -> AclEvent<'a, T>,
+    {
+        let event = event().with_version(&self.event_version);
+        let event = if self.event_masks {
+            event.with_masks(before, after)
+        } else {
+            event
+        };
+        event.emit();
+        #[cfg(feature = "event-history")]
+        self.event_history().record(&event);
+    }
+
+    #[cfg(not(feature = "events"))]
+    fn emit_event<'a, T, F>(&'a self, _event: F, _before: AclPermissions, _after: AclPermissions)
+    where
+        T: Serialize,
+        F: FnOnce() -> AclEvent<'a, T>,
+    {
+    }
+
+    /// Emits the event built by `event` for a mutation of many accounts, hence
+    /// without masks. Like [`Self::emit_event`], nothing is emitted without
+    /// feature `events`.
+    #[cfg(feature = "events")]
+    fn emit_summary_event<'a, T, F>(&'a self, event: F)
+    where
+        T: Serialize,
+        F: FnOnce() -> AclEvent<'a, T>,
+    {
+        let event = event().with_version(&self.event_version);
+        event.emit();
+        #[cfg(feature = "event-history")]
+        self.event_history().record(&event);
+    }
+
+    #[cfg(not(feature = "events"))]
+    fn emit_summary_event<'a, T, F>(&'a self, _event: F)
+    where
+        T: Serialize,
+        F: FnOnce() -> AclEvent<'a, T>,
+    {
+    }
+
+    /// Returns an error if private enumeration is enabled and the predecessor
+    /// is neither a super admin nor an admin for any role.
+    ///
+    /// Note that this gating is advisory. The predecessor is only available in
+    /// change method calls, so with private enumeration enabled enumeration
+    /// methods must be called as transactions. Contract state remains publicly
+    /// readable via RPC, hence this is no privacy guarantee.
+    fn check_enumeration_access(&self) -> Result<(), AclError> {
+        if !self.private_enumeration {
+            return Ok(());
+        }
+        let predecessor = env::predecessor_account_id();
+        if !self.is_admin_of_any_role(&predecessor) {
+            return Err(AclError::Unauthorized {
+                account_id: predecessor,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns a page of up to `limit` bearers of `permission`, skipping the
+    /// first `skip` items. Allows paginated retrieval of bearers.
+    ///
+    /// Bearers are read from the index maintained on every grant and revoke,
+    /// so only the returned page is loaded instead of every account with
+    /// permissions. The order is stable as long as the set is not modified.
+    ///
+    /// Panics if `limit` exceeds [`ACL_MAX_PAGE_SIZE`]. Fails if the
+    /// predecessor may not enumerate, see [`Self::check_enumeration_access`].
+    pub fn get_bearers(
+        &self,
+        permission: AclPermissions,
+        skip: usize,
+        limit: usize,
+    ) -> Result<AclPage<AccountId>, AclError> {
+        self.check_enumeration_access()?;
+        let set = match self.bearers.get(&permission) {
+            Some(set) => set,
+            None => return Ok(AclPage::new(std::iter::empty(), limit)),
+        };
+        Ok(AclPage::new(set.iter().skip(skip), limit))
+    }
+
+    /// Returns a page of up to `limit` accounts with their stored permissions,
+    /// skipping the first `skip` accounts. Allows a paginated snapshot of the
+    /// ACL which can be restored via [`Self::import_permissions`]. The order is
+    /// stable as long as permissions are not modified.
+    ///
+    /// Panics if `limit` exceeds [`ACL_MAX_PAGE_SIZE`]. Fails if enumeration of
+    /// accounts is disabled or if the predecessor may not enumerate, see
+    /// [`Self::check_enumeration_access`].
+    pub fn export_permissions(
+        &self,
+        skip: usize,
+        limit: usize,
+    ) -> Result<AclPage<(AccountId, AclPermissions)>, AclError> {
+        self.check_enumeration_access()?;
+        let accounts = self
+            .accounts
+            .as_ref()
+            .ok_or(AclError::EnumerationDisabled)?;
+        let entries = accounts.iter().skip(skip).map(|account_id| {
+            let permissions = self.get_or_init_permissions(&account_id);
+            (account_id, permissions)
+        });
+        Ok(AclPage::new(entries, limit))
+    }
+
+    /// Returns up to `limit` accounts which satisfy `requirement`, skipping the
+    /// first `skip` of them. Allows paginated retrieval of the accounts that
+    /// may call a guarded method.
+    ///
+    /// Accounts are evaluated like in [`Self::check_method`], i.e. against
+    /// their effective permissions. Only accounts with stored permissions are
+    /// considered, so accounts which hold a role in denylist mode without
+    /// having permissions stored are not returned.
+    ///
+    /// Every account with permissions up to the end of the page is evaluated,
+    /// so gas usage grows with `skip + limit`. `limit` is capped at
+    /// [`ACL_MAX_PAGE_SIZE`].
+    ///
+    /// Fails if enumeration of accounts is disabled or if the predecessor may
+    /// not enumerate, see [`Self::check_enumeration_access`].
+    pub fn get_authorized_accounts(
+        &self,
+        requirement: AclRequirement,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<AccountId>, AclError> {
+        self.check_enumeration_access()?;
+        let accounts = self
+            .accounts
+            .as_ref()
+            .ok_or(AclError::EnumerationDisabled)?;
+        Ok(accounts
+            .iter()
+            .filter(|account_id| {
+                requirement.is_satisfied_by(self.get_effective_permissions(account_id))
+            })
+            .skip(skip)
+            .take(limit.min(ACL_MAX_PAGE_SIZE))
+            .collect())
+    }
+
+    /// Returns all operations that await action.
+    pub fn pending_operations(&self) -> Vec<PendingOp> {
+        self.super_admin_proposal
+            .iter()
+            .cloned()
+            .map(PendingOp::SuperAdminTransfer)
+            .collect()
+    }
+
+    /// Returns an estimate of the storage bytes used by the ACL, computed from
+    /// the number of accounts with permissions and the number of bearers per
+    /// permission.
+    ///
+    /// This is an approximation meant for monitoring and budgeting, not an
+    /// exact measurement of storage usage.
+    pub fn storage_bytes(&self) -> u64 {
+        let num_bearers: u64 = self
+            .known_flags()
+            .into_iter()
+            .map(|flag| self.count_bearers(flag))
+            .sum();
+        let num_indexed: u64 = match self.accounts {
+            Some(_) => self.num_accounts,
+            None => 0,
+        };
+        ACL_STORAGE_BYTES_OVERHEAD
+            + self.num_accounts * ACL_STORAGE_BYTES_PER_ACCOUNT
+            + (num_bearers + num_indexed) * ACL_STORAGE_BYTES_PER_BEARER
+    }
+
+    /// Returns the number of accounts that have been granted `role`, including
+    /// grants which are not active according to their schedule.
+    pub fn count_grantees(&self, role: R) -> u64 {
+        self.count_bearers(role.into())
+    }
+
+    /// Returns the number of admins for `role`. Super admins are not counted,
+    /// even though they are implicitly admins for every role.
+    pub fn count_admins(&self, role: R) -> u64 {
+        self.count_bearers(role.admin_flag())
+    }
+
+    /// Returns the number of bearers of `permission`.
+    fn count_bearers(&self, permission: AclPermissions) -> u64 {
+        match self.bearers.get(&permission) {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+}
+
+/// An ACL operation that was initiated and awaits action by another account.
+/// Each variant carries the target and proposer of the operation.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PendingOp {
+    /// A super admin transfer awaiting acceptance, see
+    /// [`Acl::propose_super_admin`].
+    SuperAdminTransfer(SuperAdminProposal),
+}
+
+/// A proposal to make `new_admin` a super admin.
+#[derive(Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SuperAdminProposal {
+    /// The super admin who made the proposal.
+    proposer: AccountId,
+    /// The account which may accept the proposal.
+    new_admin: AccountId,
+}
+
+/// Summary of the permissions of an account, see [`Acl::diagnose`].
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AclDiagnostics<R> {
+    /// Roles granted to the account which are active.
+    pub roles: Vec<R>,
+    /// Roles for which the account holds the admin flag.
+    pub admin_roles: Vec<R>,
+    /// Whether the account is a super admin, which makes it admin for every
+    /// role irrespective of `admin_roles`.
+    pub is_super_admin: bool,
+}
+
+/// Time window in which a role grant is active. Timestamps are block
+/// timestamps in nanoseconds.
+#[derive(Copy, Clone, BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AclGrantSchedule {
+    /// The grant is inactive before this timestamp.
+    pub effective_at: Option<U64>,
+    /// The grant is inactive from this timestamp on.
+    pub expires_at: Option<U64>,
+}
+
+impl AclGrantSchedule {
+    /// Returns whether a grant with this schedule is active at `timestamp`.
+    fn is_active_at(&self, timestamp: u64) -> bool {
+        let has_started = match self.effective_at {
+            Some(effective_at) => effective_at.0 <= timestamp,
+            None => true,
+        };
+        let has_ended = match self.expires_at {
+            Some(expires_at) => expires_at.0 <= timestamp,
+            None => false,
+        };
+        has_started && !has_ended
+    }
+}
+
+/// A page of items returned by a paginated enumeration.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AclPage<T> {
+    /// The items on the page.
+    pub items: Vec<T>,
+    /// Whether there are further items after the page.
+    pub has_more: bool,
+}
+
+impl<T> AclPage<T> {
+    /// Collects up to `limit` of `items` into a page. Reads one item beyond
+    /// the page to determine `has_more`.
+    ///
+    /// Panics if `limit` exceeds [`ACL_MAX_PAGE_SIZE`].
+    fn new(items: impl Iterator<Item = T>, limit: usize) -> Self {
+        require!(
+            limit <= ACL_MAX_PAGE_SIZE,
+            format!(
+                "At most {} items can be requested per page",
+                ACL_MAX_PAGE_SIZE
+            ),
+        );
+        let mut items: Vec<T> = items.take(limit + 1).collect();
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        Self { items, has_more }
+    }
+
+    /// Converts the items on the page by applying `f`.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> AclPage<U> {
+        AclPage {
+            items: self.items.into_iter().map(f).collect(),
+            has_more: self.has_more,
+        }
+    }
+}
+
+/// Errors returned by fallible ACL methods.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AclError {
+    /// `account_id` lacks the permissions required for the operation.
+    Unauthorized { account_id: AccountId },
+    /// The `predecessor` is not authorized as an admin for `role`.
+    NotAdmin {
+        role: String,
+        predecessor: AccountId,
+    },
+    /// `method` is not guarded by the ACL.
+    UnguardedMethod { method: String },
+    /// No `Role` has discriminant `value`.
+    InvalidRoleDiscriminant { value: u8 },
+    /// Accounts cannot be enumerated since the `Acl` was created without
+    /// enumeration.
+    EnumerationDisabled,
+    /// No role is registered as `name`.
+    UnknownRole { name: String },
+    /// No flag of `AclPermissions` is defined at bit position `shift`.
+    UndefinedFlag { shift: u8 },
+    /// No group of roles is defined as `name`.
+    UnknownGroup { name: String },
+    /// Renouncing admin rights for `role` has no effect, since the caller
+    /// remains admin for it as super admin.
+    RedundantRenounce { role: String },
+    /// Revoking admin rights for `role` from `account_id` has no effect, since
+    /// it remains admin for `role` as super admin.
+    RedundantRevoke { account_id: AccountId, role: String },
+    /// Permissions cannot be granted to the contract itself.
+    GrantToContract,
+    /// Permissions cannot be granted to the `system` account.
+    GrantToSystemAccount,
+    /// `role` has reached its maximum of `max` admins.
+    MaxAdminsReached { role: String, max: u32 },
+    /// `role` must keep at least `min` admins.
+    MinAdminsReached { role: String, min: u32 },
+    /// `account_id` has none of the `required` permissions.
+    MissingAnyPermission {
+        account_id: AccountId,
+        required: AclPermissions,
+    },
+    /// `account_id` lacks the `missing` permissions out of the `required`
+    /// ones.
+    MissingAllPermissions {
+        account_id: AccountId,
+        missing: AclPermissions,
+        required: AclPermissions,
+    },
+}
+
+impl fmt::Display for AclError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized { account_id } => {
+                write!(f, "Account {} is not authorized", account_id)
+            }
+            Self::NotAdmin { role, predecessor } => {
+                write!(f, "Account {} is not an admin for {}", predecessor, role)
+            }
+            Self::UnguardedMethod { method } => {
+                write!(f, "Method {} is not guarded by the ACL", method)
+            }
+            Self::InvalidRoleDiscriminant { value } => {
+                write!(f, "No role has discriminant {}", value)
+            }
+            Self::EnumerationDisabled => write!(f, "Enumeration of accounts is disabled"),
+            Self::UnknownRole { name } => write!(f, "Role {} is not registered", name),
+            Self::UndefinedFlag { shift } => {
+                write!(f, "No permission flag is defined at bit {}", shift)
+            }
+            Self::UnknownGroup { name } => write!(f, "Group {} is not defined", name),
+            Self::RedundantRenounce { role } => write!(
+                f,
+                "Caller remains admin for {} as super admin, renounce super admin instead",
+                role
+            ),
+            Self::RedundantRevoke { account_id, role } => write!(
+                f,
+                "Account {} remains admin for {} as super admin, revoke super admin instead",
+                account_id, role
+            ),
+            Self::GrantToContract => {
+                write!(f, "Permissions cannot be granted to the contract itself")
+            }
+            Self::GrantToSystemAccount => {
+                write!(f, "Permissions cannot be granted to the system account")
+            }
+            Self::MaxAdminsReached { role, max } => {
+                write!(f, "Role {} has reached its maximum of {} admins", role, max)
+            }
+            Self::MinAdminsReached { role, min } => {
+                write!(f, "Role {} must keep at least {} admins", role, min)
+            }
+            Self::MissingAnyPermission {
+                account_id,
+                required,
+            } => write!(
+                f,
+                "Account {} must have at least one role of {}",
+                account_id, required
+            ),
+            Self::MissingAllPermissions {
+                account_id,
+                required,
+                ..
+            } => write!(
+                f,
+                "Account {} must have all roles in {}",
+                account_id, required
+            ),
+        }
+    }
+}
+
+impl FunctionError for AclError {
+    fn panic(&self) -> ! {
+        env::panic_str(&self.to_string())
+    }
+}
+
+// TODO probably should be the near-plugins ACL standard (if we define one)
+const EVENT_STANDARD: &str = "nep297";
+const EVENT_VERSION: &str = "1.0.0";
+/// Prefix of logged events, which lets indexers tell them apart from other
+/// log lines.
+#[cfg(feature = "events")]
+const EVENT_LOG_PREFIX: &str = "EVENT_JSON:";
+/// Default value of `Acl::event_prefix`.
+const EVENT_PREFIX: &str = "acl_";
+
+/// Returns whether `version` is of the form `MAJOR.MINOR.PATCH` with numeric
+/// components, as required for the version of NEP-297 events.
+fn is_semver_core(version: &str) -> bool {
+    let components: Vec<&str> = version.split('.').collect();
+    components.len() == 3
+        && components
+            .iter()
+            .all(|c| !c.is_empty() && c.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Represents a [NEP-297] event.
+///
+/// Events are only serialized, so fields borrow where possible to avoid
+/// allocations. In particular the affected account and the version are
+/// borrowed from the caller and the `Acl` respectively.
+///
+/// [NEP-297]: https://nomicon.io/Standards/EventsFormat
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AclEvent<'a, R> {
+    pub standard: &'static str,
+    pub version: &'a str,
+    pub event: String,
+    pub data: AclEventMetadata<'a, R>,
+}
+
+impl<'a, R> AclEvent<'a, R>
+where
+    R: Serialize,
+{
+    /// Constructor which reads predecessor's account id from the current
+    /// environment. The event name is `prefix` followed by the name of `id`.
+    /// Parameters `role` and `account_id` are passed on to
+    /// [`AclEventMetadata`].
+    fn new_from_env(prefix: &str, id: AclEventId, role: R, account_id: &'a AccountId) -> Self {
+        Self {
+            standard: EVENT_STANDARD,
+            version: EVENT_VERSION,
+            event: format!("{}{}", prefix, id.name()),
+            data: AclEventMetadata {
+                role,
+                account_id,
+                predecessor: env::predecessor_account_id(),
+                block_timestamp: env::block_timestamp().into(),
+                block_height: env::block_height().into(),
+                authorized_via: None,
+                extra: None,
+                memo: None,
+                mask_before: None,
+                mask_after: None,
+            },
+        }
+    }
+
+    /// Overrides the default version if `version` is set.
+    #[cfg(feature = "events")]
+    fn with_version(mut self, version: &'a Option<String>) -> Self {
+        if let Some(version) = version {
+            self.version = version;
+        }
+        self
+    }
+
+    /// Records the affected account's permissions before and after the
+    /// mutation as hex strings.
+    #[cfg(feature = "events")]
+    fn with_masks(mut self, before: AclPermissions, after: AclPermissions) -> Self {
+        self.data.mask_before = Some(format!("{:#x}", before.bits()));
+        self.data.mask_after = Some(format!("{:#x}", after.bits()));
+        self
+    }
+
+    /// Records the context of the action in the metadata.
+    fn with_context(mut self, context: &AclEventContext) -> Self {
+        self.data.authorized_via = context.authorized_via;
+        self.data.extra = context.extra.clone();
+        self.data.memo = context.memo.clone();
+        if let Some(predecessor) = &context.predecessor {
+            self.data.predecessor = predecessor.clone();
+        }
+        self
+    }
+
+    /// Returns the event serialized as JSON, which is logged with the prefix
+    /// `EVENT_JSON:` when the event is emitted.
+    pub fn to_json_event_string(&self) -> String {
+        serde_json::to_string(self)
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize AclEvent"))
+    }
+
+    /// Emits the event by logging to the current environment.
+    #[cfg(feature = "events")]
+    fn emit(&self) {
+        env::log_str(&format!(
+            "{}{}",
+            EVENT_LOG_PREFIX,
+            self.to_json_event_string()
+        ))
+    }
+}
+
+/// Events resulting from ACL actions.
+#[derive(Copy, Clone)]
+pub enum AclEventId {
+    AdminAdded,
+    AdminRevoked,
+    AdminRenounced,
+    RoleGranted,
+    RoleRevoked,
+    RoleRenounced,
+    RoleAndAdminGranted,
+    SuperAdminAdded,
+    SuperAdminRevoked,
+    AllRevoked,
+    ImportCompleted,
+    Reset,
+}
+
+impl AclEventId {
+    /// Returns the name to be used in the `event` field when formatting
+    /// according to NEP-297, without the event prefix (see
+    /// [`Acl::set_event_prefix`]).
+    ///
+    /// Returning `&'static str` to avoid allocations when emitting events.
+    fn name(self) -> &'static str {
+        match self {
+            Self::AdminAdded => "admin_added",
+            Self::AdminRevoked => "admin_revoked",
+            Self::AdminRenounced => "admin_renounced",
+            Self::RoleGranted => "role_granted",
+            Self::RoleRevoked => "role_revoked",
+            Self::RoleRenounced => "role_renounced",
+            Self::RoleAndAdminGranted => "role_and_admin_granted",
+            Self::SuperAdminAdded => "super_admin_added",
+            Self::SuperAdminRevoked => "super_admin_revoked",
+            Self::AllRevoked => "all_revoked",
+            Self::ImportCompleted => "import_completed",
+            Self::Reset => "reset",
+        }
+    }
+}
+
+/// Metadata emitted in NEP-297 event field `data`.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AclEventMetadata<'a, R> {
+    /// The role related to the event. For `AllRevoked` events this lists the
+    /// names of all revoked flags, for `ImportCompleted` events it is the
+    /// number of accounts whose permissions changed and for `Reset` events the
+    /// number of cleared accounts.
+    pub role: R,
+    /// The account whose permissions are affected. For `ImportCompleted` and
+    /// `Reset` events it is the contract's own account.
+    pub account_id: &'a AccountId,
+    /// The account which originated the contract call.
+    pub predecessor: AccountId,
+    /// Timestamp in nanoseconds of the block in which the event was emitted.
+    pub block_timestamp: U64,
+    /// Height of the block in which the event was emitted.
+    pub block_height: U64,
+    /// How the predecessor was authorized. Omitted for actions that did not
+    /// require authorization, e.g. renouncing permissions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_via: Option<AclAuthorizedVia>,
+    /// Custom data attached by the contract, e.g. a reason or a request id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra: Option<serde_json::Value>,
+    /// Free-form note attached by the caller, e.g. the reason for a grant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// Bitmask of the account's permissions before the mutation. Only present
+    /// if enabled via [`Acl::set_event_masks`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_before: Option<String>,
+    /// Bitmask of the account's permissions after the mutation. Only present
+    /// if enabled via [`Acl::set_event_masks`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_after: Option<String>,
+}
+
+/// Context of a mutation which is recorded in the metadata of its events.
+#[derive(Clone, Default)]
+struct AclEventContext {
+    /// How the predecessor was authorized, `None` for unchecked mutations.
+    authorized_via: Option<AclAuthorizedVia>,
+    /// Custom data to attach to the events.
+    extra: Option<serde_json::Value>,
+    /// Note of the caller to attach to the events.
+    memo: Option<String>,
+    /// Account which called the method, if it was passed explicitly instead
+    /// of being read from the environment.
+    predecessor: Option<AccountId>,
+}
+
+impl AclEventContext {
+    /// Returns the context of a mutation authorized via `authorized_via`.
+    fn authorized(authorized_via: AclAuthorizedVia) -> Self {
+        Self {
+            authorized_via: Some(authorized_via),
+            ..Self::default()
+        }
+    }
+
+    /// Attaches `extra` as custom data.
+    fn with_extra(mut self, extra: serde_json::Value) -> Self {
+        self.extra = Some(extra);
+        self
+    }
+
+    /// Attaches a `memo` of the caller.
+    fn with_memo(mut self, memo: String) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    /// Records `predecessor` as the account which called the method.
+    fn with_predecessor(mut self, predecessor: &AccountId) -> Self {
+        self.predecessor = Some(predecessor.clone());
+        self
+    }
+}
+
+/// The permission that authorized the predecessor to perform an action.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AclAuthorizedVia {
+    /// Authorized by [`AclPermissions::SUPER_ADMIN`].
+    Super,
+    /// Authorized by the admin flag of the affected `Role`.
+    RoleAdmin,
+    /// Authorized as delegated admin of the affected `Role`, see
+    /// [`Acl::set_delegated_admin`].
+    Delegated,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_context, set_predecessor};
+    #[cfg(feature = "events")]
+    use crate::test_utils::{logged_event_names, logged_events};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    /// Returns an `Acl` with `accounts(0)` as super admin, who is the
+    /// predecessor afterwards. Events of the setup are not logged.
+    fn setup_acl() -> Acl<Role> {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::new();
+        acl.add_super_admin_unchecked(&accounts(0));
+        set_predecessor(accounts(0));
+        acl
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn grant_role_requires_admin() {
+        let mut acl = setup_acl();
+        acl.add_admin_unchecked(Role::L1, &accounts(1));
+
+        set_predecessor(accounts(2));
+        assert_eq!(acl.grant_role(Role::L1, &accounts(3), &accounts(2)), None);
+        assert!(!acl.has_role(Role::L1, &accounts(3)));
+
+        set_predecessor(accounts(1));
+        assert_eq!(
+            acl.grant_role(Role::L1, &accounts(3), &accounts(1)),
+            Some(true)
+        );
+        assert!(acl.has_role(Role::L1, &accounts(3)));
+        let events = logged_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event"], "acl_role_granted");
+        assert_eq!(events[0]["data"]["account_id"], accounts(3).as_str());
+        assert_eq!(events[0]["data"]["predecessor"], accounts(1).as_str());
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn role_admin_cannot_grant_other_roles() {
+        let mut acl = setup_acl();
+        acl.add_admin_unchecked(Role::L1, &accounts(1));
+
+        set_predecessor(accounts(1));
+        assert_eq!(acl.grant_role(Role::L2, &accounts(3), &accounts(1)), None);
+        assert_eq!(acl.add_admin(Role::L2, &accounts(3), &accounts(1)), None);
+        assert_eq!(
+            acl.add_admin(Role::L1, &accounts(3), &accounts(1)),
+            Some(true)
+        );
+        assert_eq!(logged_event_names(), vec!["acl_admin_added"]);
+    }
+
+    #[test]
+    fn super_admin_can_grant_every_role() {
+        let mut acl = setup_acl();
+        for role in Role::all() {
+            assert_eq!(acl.grant_role(role, &accounts(1), &accounts(0)), Some(true));
+            assert_eq!(acl.add_admin(role, &accounts(1), &accounts(0)), Some(true));
+        }
+    }
+
+    // The admin checks branch on `AclPermissions` only, so the following tests
+    // need no context.
+
+    #[test]
+    fn admin_authorization_attributes_super_admin_first() {
+        let permissions = AclPermissions::SUPER_ADMIN | AclPermissions::L1_ADMIN;
+        for role in Role::all() {
+            assert_eq!(
+                permissions.admin_authorization(role),
+                Some(AclAuthorizedVia::Super),
+            );
+        }
+    }
+
+    #[test]
+    fn admin_authorization_of_role_admin() {
+        let permissions = AclPermissions::L1 | AclPermissions::L2_ADMIN;
+        assert_eq!(permissions.admin_authorization(Role::L1), None);
+        assert_eq!(
+            permissions.admin_authorization(Role::L2),
+            Some(AclAuthorizedVia::RoleAdmin),
+        );
+        assert_eq!(permissions.admin_authorization(Role::L3), None);
+        assert_eq!(AclPermissions::empty().admin_authorization(Role::L1), None);
+    }
+
+    #[test]
+    fn requirement_is_satisfied_by() {
+        let any = AclRequirement::from_roles(&[Role::L1, Role::L3], false);
+        let all = AclRequirement::from_roles(&[Role::L1, Role::L3], true);
+        let l1 = AclPermissions::L1;
+        let l1_l3 = AclPermissions::L1 | AclPermissions::L3;
+        assert!(any.is_satisfied_by(l1));
+        assert!(!all.is_satisfied_by(l1));
+        assert!(all.is_satisfied_by(l1_l3));
+        assert!(!any.is_satisfied_by(AclPermissions::L2 | AclPermissions::L1_ADMIN));
+        // Admin flags do not satisfy requirements of roles.
+        assert!(!any.is_satisfied_by(AclPermissions::SUPER_ADMIN));
+    }
+
+    #[test]
+    fn migrate_from_v0() {
+        set_predecessor(accounts(0));
+        let key = |specifier| acl_new_storage_prefix(ACL_STORAGE_PREFIX, specifier);
+        let mut old = AclV0 {
+            permissions: UnorderedMap::new(key(AclStorageKey::Permissions)),
+            bearers: UnorderedMap::new(key(AclStorageKey::Bearers)),
+        };
+        let entries = [
+            (accounts(0), AclPermissions::L1_ADMIN),
+            (accounts(1), AclPermissions::L1),
+        ];
+        for (account_id, flag) in entries {
+            old.permissions.insert(&account_id, &flag);
+            let mut set = UnorderedSet::new(key(AclStorageKey::BearersSet { permission: flag }));
+            set.insert(&account_id);
+            old.bearers.insert(&flag, &set);
+        }
+
+        let mut acl = Acl::<Role>::from(old);
+        assert!(acl.is_admin(Role::L1, &accounts(0)));
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert_eq!(acl.count_grantees(Role::L1), 1);
+        assert_eq!(acl.export_permissions(0, 10).ok().unwrap().items.len(), 2);
+        // Fields added after version 0 have their defaults.
+        assert_eq!(acl.max_admins(Role::L1), None);
+        assert_eq!(acl.min_admins(Role::L1), None);
+        assert_eq!(acl.role_mode(Role::L1), AclRoleMode::Allowlist);
+        assert!(!acl.is_delegated_admin(Role::L1, &accounts(1)));
+        assert!(!acl.has_super_admin());
+
+        assert_eq!(
+            acl.grant_role(Role::L1, &accounts(2), &accounts(0)),
+            Some(true)
+        );
+        assert_eq!(acl.count_grantees(Role::L1), 2);
+    }
+
+    #[test]
+    fn has_super_admin_tracks_adds_and_revokes() {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<Role>::new();
+        assert!(!acl.has_super_admin());
+
+        acl.add_super_admin_unchecked(&accounts(0));
+        assert!(acl.has_super_admin());
+        assert_eq!(acl.add_super_admin(&accounts(1)), Some(true));
+        assert_eq!(acl.revoke_super_admin(&accounts(0)), Some(true));
+        assert!(acl.has_super_admin());
+        assert!(acl.is_super_admin(&accounts(1)));
+    }
+
+    #[test]
+    fn private_enumeration_rejects_non_admins() {
+        let mut acl = setup_acl();
+        acl.grant_role_unchecked(Role::L1, &accounts(1));
+        acl.add_admin_unchecked(Role::L2, &accounts(2));
+        assert_eq!(acl.set_private_enumeration(true), Some(true));
+
+        set_predecessor(accounts(1));
+        assert_eq!(
+            acl.get_bearers(Role::L1.into(), 0, 10).err(),
+            Some(AclError::Unauthorized {
+                account_id: accounts(1)
+            }),
+        );
+        assert_eq!(acl.set_private_enumeration(false), None);
+
+        // Admins of any role may enumerate.
+        set_predecessor(accounts(2));
+        let page = acl.get_bearers(Role::L1.into(), 0, 10).unwrap();
+        assert_eq!(page.items, vec![accounts(1)]);
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn events_record_authorization() {
+        let mut acl = setup_acl();
+        acl.add_admin_unchecked(Role::L1, &accounts(0));
+        acl.add_admin_unchecked(Role::L1, &accounts(1));
+
+        // A super admin that is also admin for the role is attributed to
+        // `Super`.
+        set_predecessor(accounts(0));
+        acl.grant_role(Role::L1, &accounts(2), &accounts(0));
+        assert_eq!(logged_events()[0]["data"]["authorized_via"], "Super");
+
+        set_predecessor(accounts(1));
+        acl.grant_role(Role::L1, &accounts(3), &accounts(1));
+        assert_eq!(logged_events()[0]["data"]["authorized_via"], "RoleAdmin");
+
+        // Unchecked mutations record no authorization.
+        acl.grant_role_unchecked(Role::L1, &accounts(4));
+        assert!(logged_events()[1]["data"].get("authorized_via").is_none());
+    }
+
+    #[test]
+    fn role_display_order_reshuffles_views() {
+        let mut acl = setup_acl();
+        for role in Role::all() {
+            acl.grant_role_unchecked(role, &accounts(1));
+        }
+        assert_eq!(
+            acl.get_roles(&accounts(1)),
+            vec![Role::L1, Role::L2, Role::L3]
+        );
+
+        let order = vec![Role::L3, Role::L1, Role::L2];
+        assert_eq!(acl.set_role_display_order(Some(order.clone())), Some(true));
+        assert_eq!(acl.role_display_order(), order);
+        assert_eq!(acl.get_roles(&accounts(1)), order);
+        assert_eq!(acl.get_admin_roles(&accounts(0)), order);
+
+        assert_eq!(acl.set_role_display_order(None), Some(true));
+        assert_eq!(
+            acl.get_roles(&accounts(1)),
+            vec![Role::L1, Role::L2, Role::L3]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Role display order must contain every role exactly once")]
+    fn role_display_order_requires_every_role() {
+        let mut acl = setup_acl();
+        acl.set_role_display_order(Some(vec![Role::L1, Role::L1, Role::L2]));
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn event_masks_bracket_the_change() {
+        let mut acl = setup_acl();
+        acl.grant_role_unchecked(Role::L2, &accounts(1));
+        set_predecessor(accounts(0));
+        assert_eq!(
+            acl.grant_role(Role::L1, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert!(logged_events()[0]["data"].get("mask_before").is_none());
+
+        assert_eq!(acl.set_event_masks(true), Some(true));
+        set_predecessor(accounts(0));
+        acl.revoke_role(Role::L1, &accounts(1), &accounts(0));
+        let data = &logged_events()[0]["data"];
+        assert_eq!(data["mask_before"], "0xa");
+        assert_eq!(data["mask_after"], "0x8");
+    }
+
+    #[test]
+    fn renounce_admin_is_rejected_for_super_admins() {
+        let mut acl = setup_acl();
+        acl.add_admin_unchecked(Role::L1, &accounts(0));
+
+        assert_eq!(
+            acl.renounce_admin(Role::L1, false),
+            Err(AclError::RedundantRenounce {
+                role: "L1".to_string()
+            }),
+        );
+        assert!(acl
+            .get_or_init_permissions(&accounts(0))
+            .contains(AclPermissions::L1_ADMIN));
+        assert!(acl.is_admin(Role::L1, &accounts(0)));
+    }
+
+    #[test]
+    fn get_flags_lists_every_defined_flag() {
+        let mut acl = setup_acl();
+        acl.grant_role_unchecked(Role::L1, &accounts(1));
+        acl.add_admin_unchecked(Role::L3, &accounts(1));
+
+        let expected: BTreeMap<String, bool> = [
+            ("SUPER_ADMIN", false),
+            ("L1", true),
+            ("L1_ADMIN", false),
+            ("L2", false),
+            ("L2_ADMIN", false),
+            ("L3", false),
+            ("L3_ADMIN", true),
+        ]
+        .into_iter()
+        .map(|(name, is_set)| (name.to_string(), is_set))
+        .collect();
+        assert_eq!(acl.get_flags(&accounts(1)), expected);
+    }
+
+    #[test]
+    fn role_layout_of_role_is_valid() {
+        set_predecessor(accounts(0));
+        Acl::<Role>::assert_role_layout();
+        for role in Role::all() {
+            let flag: AclPermissions = role.into();
+            assert_ne!(flag, role.admin_flag());
+        }
+    }
+
+    /// A role whose admin flag collides with the flag of the role itself.
+    #[derive(Copy, Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct CollidingRole;
+
+    impl From<CollidingRole> for AclPermissions {
+        fn from(_: CollidingRole) -> Self {
+            AclPermissions::L1
+        }
+    }
+
+    impl AclRole for CollidingRole {
+        fn all_roles() -> Vec<Self> {
+            vec![CollidingRole]
+        }
+
+        fn admin_flag(self) -> AclPermissions {
+            AclPermissions::L1
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Flags of Role CollidingRole collide with other flags")]
+    fn role_layout_rejects_colliding_flags() {
+        set_predecessor(accounts(0));
+        Acl::<CollidingRole>::new();
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn combine_grant_events() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.grant_role_and_admin(Role::L1, &accounts(1)), Some(true));
+        assert_eq!(
+            logged_event_names(),
+            vec!["acl_role_granted", "acl_admin_added"]
+        );
+
+        assert_eq!(acl.set_combine_grant_events(true), Some(true));
+        set_predecessor(accounts(0));
+        assert_eq!(acl.grant_role_and_admin(Role::L2, &accounts(1)), Some(true));
+        let events = logged_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event"], "acl_role_and_admin_granted");
+        assert_eq!(events[0]["data"]["role"], "L2");
+        assert!(acl.has_role(Role::L2, &accounts(1)));
+        assert!(acl.is_admin(Role::L2, &accounts(1)));
+    }
+
+    #[test]
+    fn storage_bytes_follow_grants_and_revokes() {
+        let mut acl = setup_acl();
+        let initial = acl.storage_bytes();
+
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        let after_first_grant = acl.storage_bytes();
+        assert!(after_first_grant > initial);
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+        let after_second_grant = acl.storage_bytes();
+        assert!(after_second_grant > after_first_grant);
+
+        acl.revoke_role(Role::L2, &accounts(1), &accounts(0));
+        assert_eq!(acl.storage_bytes(), after_first_grant);
+        // Revoking the last role removes the account's entry.
+        acl.revoke_role(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(acl.storage_bytes(), initial);
+    }
+
+    #[test]
+    fn check_batch_reports_each_account() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L1, &accounts(2), &accounts(0));
+        acl.grant_role(Role::L2, &accounts(2), &accounts(0));
+        let account_ids = vec![accounts(1), accounts(2), accounts(3)];
+
+        let any = AclRequirement::from_roles(&[Role::L1, Role::L2], false);
+        assert_eq!(acl.check_batch(any, &account_ids), vec![true, true, false]);
+        let all = AclRequirement::from_roles(&[Role::L1, Role::L2], true);
+        assert_eq!(acl.check_batch(all, &account_ids), vec![false, true, false]);
+    }
+
+    #[test]
+    fn pending_operations_list_super_admin_transfers() {
+        let mut acl = setup_acl();
+        assert!(acl.pending_operations().is_empty());
+
+        assert_eq!(acl.propose_super_admin(accounts(1)), Some(false));
+        let pending = serde_json::to_value(acl.pending_operations()).unwrap();
+        assert_eq!(
+            pending,
+            serde_json::json!([{
+                "SuperAdminTransfer": {
+                    "proposer": accounts(0),
+                    "new_admin": accounts(1),
+                }
+            }])
+        );
+
+        set_predecessor(accounts(1));
+        assert!(acl.accept_super_admin());
+        assert!(acl.pending_operations().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn revoke_admin_removes_the_flag() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        set_predecessor(accounts(0));
+
+        assert_eq!(
+            acl.revoke_admin(Role::L1, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert!(!acl.is_admin(Role::L1, &accounts(1)));
+        assert_eq!(logged_event_names(), vec!["acl_admin_revoked"]);
+
+        // Revoking from a non-admin is a no-op.
+        set_predecessor(accounts(0));
+        assert_eq!(
+            acl.revoke_admin(Role::L1, &accounts(1), &accounts(0)),
+            Some(false)
+        );
+        assert!(logged_events().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn events_follow_nep297() {
+        set_predecessor(accounts(0));
+        let account_id = accounts(1);
+        let event = AclEvent::new_from_env("acl_", AclEventId::RoleGranted, Role::L1, &account_id);
+        let json: serde_json::Value = serde_json::from_str(&event.to_json_event_string()).unwrap();
+        assert_eq!(json["standard"], "nep297");
+        assert_eq!(json["event"], "acl_role_granted");
+
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(logged_events()[0]["standard"], "nep297");
+    }
+
+    #[test]
+    fn try_check_reports_missing_permissions() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        let target = AclPermissions::L1 | AclPermissions::L2;
+
+        assert_eq!(acl.try_check_any(target, &accounts(1)), Ok(()));
+        assert_eq!(
+            acl.try_check_any(target, &accounts(2)),
+            Err(AclError::MissingAnyPermission {
+                account_id: accounts(2),
+                required: target,
+            })
+        );
+
+        assert_eq!(acl.try_check_all(AclPermissions::L1, &accounts(1)), Ok(()));
+        assert_eq!(
+            acl.try_check_all(target, &accounts(1)),
+            Err(AclError::MissingAllPermissions {
+                account_id: accounts(1),
+                missing: AclPermissions::L2,
+                required: target,
+            })
+        );
+    }
+
+    #[test]
+    fn prefixed_instances_are_independent() {
+        set_predecessor(accounts(0));
+        let mut first = Acl::<Role>::with_prefix(b"first".to_vec(), false);
+        let mut second = Acl::<Role>::with_prefix(b"second".to_vec(), false);
+        first.add_super_admin_unchecked(&accounts(0));
+        assert!(!second.is_super_admin(&accounts(0)));
+
+        first.grant_role(Role::L1, &accounts(1), &accounts(0));
+        assert!(first.has_role(Role::L1, &accounts(1)));
+        assert!(!second.has_role(Role::L1, &accounts(1)));
+        assert_eq!(
+            second.grant_role(Role::L1, &accounts(1), &accounts(0)),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn event_prefix_is_configurable() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(logged_event_names(), vec!["acl_role_granted"]);
+
+        assert_eq!(acl.set_event_prefix("counter_acl_".to_string()), Some(true));
+        set_predecessor(accounts(0));
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+        assert_eq!(logged_event_names(), vec!["counter_acl_role_granted"]);
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn extra_data_round_trips() {
+        let mut acl = setup_acl();
+        let extra = serde_json::json!({ "request_id": 42, "reason": "onboarding" });
+        acl.grant_role_with_extra(Role::L1, &accounts(1), extra.clone());
+        assert_eq!(logged_events()[0]["data"]["extra"], extra);
+
+        // Without extra data the field is omitted.
+        set_predecessor(accounts(0));
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+        let events = logged_events();
+        assert!(events[0]["data"].get("extra").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn super_admin_is_admin_for_every_role() {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<Role>::new();
+        assert!(acl.add_super_admin_unchecked(&accounts(1)));
+        assert_eq!(logged_event_names(), vec!["acl_super_admin_added"]);
+        for role in Role::all() {
+            assert!(acl.is_admin(role, &accounts(1)));
+        }
+    }
+
+    #[test]
+    fn is_super_admin_ignores_role_admins() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert!(acl.is_admin(Role::L1, &accounts(1)));
+        assert!(!acl.is_super_admin(&accounts(1)));
+
+        assert!(acl.is_super_admin(&accounts(0)));
+        assert!(!acl.is_super_admin(&accounts(2)));
+    }
+
+    #[test]
+    fn revoke_super_admin_keeps_the_last_one() {
+        let mut acl = setup_acl();
+        acl.add_super_admin(&accounts(1));
+        assert_eq!(acl.revoke_super_admin(&accounts(1)), Some(true));
+        assert!(!acl.is_super_admin(&accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot revoke the last super admin")]
+    fn revoke_super_admin_rejects_the_last_one() {
+        let mut acl = setup_acl();
+        acl.revoke_super_admin(&accounts(0));
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn super_admin_transfer_takes_two_steps() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.propose_super_admin(accounts(1)), Some(false));
+        assert!(!acl.is_super_admin(&accounts(1)));
+        // A new proposal overwrites the pending one.
+        assert_eq!(acl.propose_super_admin(accounts(2)), Some(true));
+
+        set_predecessor(accounts(2));
+        assert!(acl.accept_super_admin());
+        assert!(acl.is_super_admin(&accounts(2)));
+        assert!(!acl.is_super_admin(&accounts(1)));
+        assert_eq!(logged_event_names(), vec!["acl_super_admin_added"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Caller is not the proposed super admin")]
+    fn super_admin_transfer_rejects_other_accounts() {
+        let mut acl = setup_acl();
+        acl.propose_super_admin(accounts(1));
+        set_predecessor(accounts(2));
+        acl.accept_super_admin();
+    }
+
+    #[test]
+    fn grant_role_to_many_reports_new_grants() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(2), &accounts(0));
+        let account_ids = vec![accounts(1), accounts(2), accounts(3)];
+        assert_eq!(
+            acl.grant_role_to_many(Role::L1, &account_ids),
+            Some(vec![true, false, true])
+        );
+        assert!(account_ids
+            .iter()
+            .all(|account_id| acl.has_role(Role::L1, account_id)));
+
+        set_predecessor(accounts(1));
+        assert_eq!(acl.grant_role_to_many(Role::L1, &[accounts(4)]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn revoke_role_from_many_reports_first_occurrences() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L1, &accounts(2), &accounts(0));
+        set_predecessor(accounts(0));
+
+        let account_ids = vec![accounts(1), accounts(3), accounts(2), accounts(1)];
+        assert_eq!(
+            acl.revoke_role_from_many(Role::L1, &account_ids),
+            Some(vec![true, false, true, false])
+        );
+        assert!(!acl.has_role(Role::L1, &accounts(1)));
+        assert!(!acl.has_role(Role::L1, &accounts(2)));
+        assert_eq!(
+            logged_event_names(),
+            vec!["acl_role_revoked", "acl_role_revoked"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn revoke_all_removes_the_entry() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L3, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        set_predecessor(accounts(0));
+
+        assert_eq!(acl.revoke_all(&accounts(1)), Some(true));
+        assert!(acl.get_or_init_permissions(&accounts(1)).is_empty());
+        assert!(acl.permissions.get(&accounts(1)).is_none());
+        let events = logged_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event"], "acl_all_revoked");
+        assert_eq!(
+            events[0]["data"]["role"],
+            serde_json::json!(["L1", "L2_ADMIN", "L3"])
+        );
+
+        assert_eq!(acl.revoke_all(&accounts(1)), Some(false));
+    }
+
+    #[test]
+    fn permissions_display_flag_names() {
+        let permissions =
+            AclPermissions::L3_ADMIN | AclPermissions::SUPER_ADMIN | AclPermissions::L1;
+        assert_eq!(permissions.to_string(), "SUPER_ADMIN | L1 | L3_ADMIN");
+        assert_eq!(AclPermissions::empty().to_string(), "(empty)");
+    }
+
+    #[test]
+    fn has_any_and_all_roles() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+
+        assert!(acl.has_any_role(&[Role::L1, Role::L3], &accounts(1)));
+        assert!(!acl.has_all_roles(&[Role::L1, Role::L3], &accounts(1)));
+        assert!(acl.has_all_roles(&[Role::L1, Role::L2], &accounts(1)));
+        assert!(!acl.has_any_role(&[Role::L1, Role::L3], &accounts(2)));
+    }
+
+    #[test]
+    fn self_targeting_keeps_permissions_consistent() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+
+        assert_eq!(
+            acl.grant_role(Role::L1, &accounts(1), &accounts(1)),
+            Some(true)
+        );
+        assert_eq!(
+            acl.add_admin(Role::L1, &accounts(1), &accounts(1)),
+            Some(false)
+        );
+        assert_eq!(
+            acl.get_or_init_permissions(&accounts(1)),
+            AclPermissions::L1 | AclPermissions::L1_ADMIN
+        );
+
+        assert_eq!(
+            acl.revoke_role(Role::L1, &accounts(1), &accounts(1)),
+            Some(true)
+        );
+        assert_eq!(
+            acl.revoke_admin(Role::L1, &accounts(1), &accounts(1)),
+            Some(true)
+        );
+        assert!(acl.get_or_init_permissions(&accounts(1)).is_empty());
+        assert_eq!(acl.grant_role(Role::L1, &accounts(1), &accounts(1)), None);
+    }
+
+    #[test]
+    fn registered_roles_use_free_flags() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.register_role("L4".to_string()), Some(true));
+        assert_eq!(acl.register_role("L4".to_string()), Some(false));
+        let flag = acl.dynamic_role_flag("L4").unwrap();
+        assert!(!AclPermissions::all().intersects(flag));
+
+        assert_eq!(acl.grant_role_by_name("L4", &accounts(1)), Ok(Some(true)));
+        assert_eq!(acl.has_role_by_name("L4", &accounts(1)), Ok(true));
+        assert_eq!(acl.get_permission_bits(&accounts(1)), flag.bits());
+        assert!(Role::all()
+            .into_iter()
+            .all(|role| !acl.has_role(role, &accounts(1))));
+        assert_eq!(
+            acl.has_role_by_name("L5", &accounts(1)),
+            Err(AclError::UnknownRole {
+                name: "L5".to_string()
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn registered_roles_share_the_mutations_of_static_roles() {
+        let mut acl = setup_acl();
+        acl.register_role("L4".to_string());
+        assert_eq!(acl.grant_role_by_name("L4", &accounts(1)), Ok(Some(true)));
+        assert_eq!(acl.add_admin_by_name("L4", &accounts(1)), Ok(Some(true)));
+        assert_eq!(acl.revoke_role_by_name("L4", &accounts(1)), Ok(Some(true)));
+        assert_eq!(acl.revoke_role_by_name("L4", &accounts(1)), Ok(Some(false)));
+        assert_eq!(acl.has_role_by_name("L4", &accounts(1)), Ok(false));
+
+        let events = logged_events();
+        let names: Vec<_> = events.iter().map(|event| &event["event"]).collect();
+        assert_eq!(
+            names,
+            vec!["acl_role_granted", "acl_admin_added", "acl_role_revoked"]
+        );
+        assert!(events.iter().all(
+            |event| event["data"]["role"] == "L4" && event["data"]["authorized_via"] == "Super"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Permissions cannot be granted to the contract itself")]
+    fn onboard_rejects_the_contract() {
+        let mut acl = setup_acl();
+        acl.set_auto_grant_role(Some(Role::L1));
+        acl.onboard(&env::current_account_id());
+    }
+
+    #[test]
+    fn onboard_keeps_denials() {
+        let mut acl = setup_acl();
+        acl.set_role_mode(Role::L1, AclRoleMode::Denylist);
+        acl.set_auto_grant_role(Some(Role::L1));
+        acl.revoke_role(Role::L1, &accounts(1), &accounts(0));
+        assert!(!acl.onboard(&accounts(1)));
+        assert!(!acl.has_role(Role::L1, &accounts(1)));
+        assert!(acl.onboard(&accounts(2)));
+        assert!(acl.has_role(Role::L1, &accounts(2)));
+    }
+
+    #[test]
+    fn role_from_discriminant() {
+        assert_eq!(Role::try_from(0), Ok(Role::L1));
+        assert_eq!(Role::try_from(2), Ok(Role::L3));
+        assert_eq!(
+            Role::try_from(99),
+            Err(AclError::InvalidRoleDiscriminant { value: 99 })
+        );
+        assert_eq!(Role::all(), [Role::L1, Role::L2, Role::L3]);
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn renounce_emits_renounce_events() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+
+        assert!(acl.renounce_role(Role::L1));
+        assert_eq!(acl.renounce_admin(Role::L2, false), Ok(true));
+        let events = logged_events();
+        let names: Vec<_> = events.iter().map(|event| &event["event"]).collect();
+        assert_eq!(names, vec!["acl_role_renounced", "acl_admin_renounced"]);
+        assert!(events
+            .iter()
+            .all(|event| event["data"]["predecessor"] == event["data"]["account_id"]));
+    }
+
+    /// A role enum of a contract other than `Counter`.
+    #[derive(Copy, Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    enum VaultRole {
+        Depositor,
+        Auditor,
+    }
+
+    impl From<VaultRole> for AclPermissions {
+        fn from(role: VaultRole) -> Self {
+            match role {
+                VaultRole::Depositor => AclPermissions::L1,
+                VaultRole::Auditor => AclPermissions::L2,
+            }
+        }
+    }
+
+    impl AclRole for VaultRole {
+        fn all_roles() -> Vec<Self> {
+            vec![VaultRole::Depositor, VaultRole::Auditor]
+        }
+
+        fn admin_flag(self) -> AclPermissions {
+            match self {
+                VaultRole::Depositor => AclPermissions::L1_ADMIN,
+                VaultRole::Auditor => AclPermissions::L2_ADMIN,
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn acl_with_custom_roles() {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<VaultRole>::with_prefix(b"vault".to_vec(), false);
+        acl.add_admin_unchecked(VaultRole::Auditor, &accounts(0));
+
+        assert_eq!(
+            acl.grant_role(VaultRole::Auditor, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert_eq!(
+            acl.grant_role(VaultRole::Depositor, &accounts(1), &accounts(0)),
+            None
+        );
+        assert_eq!(acl.get_roles(&accounts(1)), vec![VaultRole::Auditor]);
+        assert_eq!(logged_events()[0]["data"]["role"], "Auditor");
+    }
+
+    #[test]
+    fn require_roles_of_predecessor() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+
+        // Super admins pass regardless of their roles.
+        acl.require_any_role(&[Role::L3]);
+        acl.require_all_roles(&[Role::L1, Role::L3]);
+
+        set_predecessor(accounts(1));
+        acl.require_any_role(&[Role::L2, Role::L3]);
+        acl.require_all_roles(&[Role::L1, Role::L2]);
+
+        // The messages `require_*` panic with if the predecessor lacks roles.
+        let target = AclPermissions::from_roles(&[Role::L1, Role::L3]);
+        assert_eq!(
+            acl.try_check_all(target, &accounts(1))
+                .unwrap_err()
+                .to_string(),
+            "Account bob must have all roles in L1 | L3"
+        );
+        assert_eq!(
+            acl.try_check_any(AclPermissions::L3, &accounts(2))
+                .unwrap_err()
+                .to_string(),
+            "Account charlie must have at least one role of L3"
+        );
+    }
+
+    /// Advances the block timestamp to `timestamp`, with `accounts(0)` as
+    /// predecessor.
+    fn set_block_timestamp(timestamp: u64) {
+        testing_env!(get_context(accounts(0)).block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn expired_grants_are_absent() {
+        let mut acl = setup_acl();
+        set_block_timestamp(100);
+        assert_eq!(
+            acl.grant_role_until(Role::L1, &accounts(1), 200),
+            Some(true)
+        );
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+
+        set_block_timestamp(200);
+        assert!(!acl.has_role(Role::L1, &accounts(1)));
+        assert!(!acl.is_authorized_any(AclPermissions::L1, &accounts(1)));
+        // Granting again replaces the expired grant.
+        assert_eq!(
+            acl.grant_role_until(Role::L1, &accounts(1), 300),
+            Some(true)
+        );
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+    }
+
+    #[test]
+    fn delayed_grants_become_active() {
+        let mut acl = setup_acl();
+        set_block_timestamp(100);
+        assert_eq!(acl.grant_role_from(Role::L2, &accounts(1), 150), Some(true));
+        assert!(!acl.has_role(Role::L2, &accounts(1)));
+        assert!(acl.role_schedule(Role::L2, &accounts(1)).is_some());
+
+        set_block_timestamp(150);
+        assert!(acl.has_role(Role::L2, &accounts(1)));
+        assert!(acl.is_authorized_all(AclPermissions::L2, &accounts(1)));
+    }
+
+    #[test]
+    fn count_grantees_ignores_repeated_changes() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.count_grantees(Role::L1), 0);
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L1, &accounts(2), &accounts(0));
+        assert_eq!(acl.count_grantees(Role::L1), 2);
+        assert_eq!(acl.count_grantees(Role::L2), 0);
+
+        acl.revoke_role(Role::L1, &accounts(1), &accounts(0));
+        acl.revoke_role(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(acl.count_grantees(Role::L1), 1);
+    }
+
+    #[test]
+    fn count_admins_excludes_super_admins() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.count_admins(Role::L1), 0);
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(acl.count_admins(Role::L1), 1);
+        acl.add_admin(Role::L1, &accounts(2), &accounts(0));
+        assert_eq!(acl.count_admins(Role::L1), 2);
+        acl.revoke_admin(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(acl.count_admins(Role::L1), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn super_admin_events_carry_metadata() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+        assert_eq!(acl.add_super_admin(&accounts(2)), None);
+        assert!(!acl.is_super_admin(&accounts(2)));
+        assert!(logged_events().is_empty());
+
+        set_predecessor(accounts(0));
+        assert_eq!(acl.add_super_admin(&accounts(2)), Some(true));
+        assert_eq!(acl.revoke_super_admin(&accounts(2)), Some(true));
+        let events = logged_events();
+        assert_eq!(events.len(), 2);
+        for (event, name) in events
+            .iter()
+            .zip(["acl_super_admin_added", "acl_super_admin_revoked"])
+        {
+            assert_eq!(event["event"], name);
+            assert_eq!(event["data"]["account_id"], "charlie");
+            assert_eq!(event["data"]["predecessor"], "alice");
+            assert_eq!(event["data"]["authorized_via"], "Super");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn transfer_admin_moves_the_flag() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+
+        assert_eq!(
+            acl.transfer_admin(Role::L2, &accounts(1), &accounts(2)),
+            Some(true)
+        );
+        assert!(!acl.is_admin(Role::L2, &accounts(1)));
+        assert!(acl.is_admin(Role::L2, &accounts(2)));
+        assert_eq!(
+            logged_event_names(),
+            vec!["acl_admin_revoked", "acl_admin_added"]
+        );
+
+        // `accounts(1)` is no longer an admin and may not transfer back.
+        assert_eq!(
+            acl.transfer_admin(Role::L2, &accounts(2), &accounts(1)),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "remains admin for L2 as super admin")]
+    fn transfer_admin_rejects_super_admins() {
+        let mut acl = setup_acl();
+        acl.add_admin_unchecked(Role::L2, &accounts(0));
+        acl.transfer_admin(Role::L2, &accounts(0), &accounts(1));
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn mutations_log_events() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(
+            logged_event_names(),
+            vec!["acl_role_granted", "acl_admin_added"]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "events"))]
+    fn mutations_log_nothing() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert!(near_sdk::test_utils::get_logs().is_empty());
+    }
+
+    #[test]
+    fn grant_role_rejects_the_contract() {
+        let mut acl = setup_acl();
+        let contract = env::current_account_id();
+        assert_eq!(
+            acl.try_grant_role(Role::L1, &contract, &accounts(0)),
+            Err(AclError::GrantToContract)
+        );
+        assert_eq!(acl.grant_role(Role::L1, &contract, &accounts(0)), None);
+        assert!(!acl.has_role(Role::L1, &contract));
+    }
+
+    #[test]
+    fn add_admin_rejects_the_system_account() {
+        let mut acl = setup_acl();
+        let system: AccountId = "system".parse().unwrap();
+        assert_eq!(
+            acl.try_add_admin(Role::L1, &system, &accounts(0)),
+            Err(AclError::GrantToSystemAccount)
+        );
+        assert_eq!(acl.add_admin(Role::L1, &system, &accounts(0)), None);
+        assert!(!acl.is_admin(Role::L1, &system));
+    }
+
+    #[test]
+    #[should_panic(expected = "Permissions cannot be granted to the system account")]
+    fn grant_role_with_memo_rejects_the_system_account() {
+        let mut acl = setup_acl();
+        acl.grant_role_with_memo(Role::L1, &"system".parse().unwrap(), "memo".to_string());
+    }
+
+    #[test]
+    fn super_admin_with_role_admin_flag() {
+        let mut acl = setup_acl();
+        acl.add_super_admin(&accounts(1));
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert!(acl.is_admin(Role::L1, &accounts(1)));
+
+        // Revoking the role admin flag alone would leave `accounts(1)` admin via
+        // super admin, so the super admin permissions are revoked first.
+        assert_eq!(acl.revoke_super_admin(&accounts(1)), Some(true));
+        assert!(acl.is_admin(Role::L1, &accounts(1)));
+        assert_eq!(
+            acl.revoke_admin(Role::L1, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert!(!acl.is_admin(Role::L1, &accounts(1)));
+    }
+
+    #[test]
+    fn check_role_passes_super_admins() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L2, &accounts(2), &accounts(0));
+
+        acl.check_role(Role::L2, &accounts(1));
+        // Unlike `check_any`, which requires the role itself.
+        acl.check_role(Role::L2, &accounts(0));
+        assert!(!acl.is_authorized_any(Role::L2.into(), &accounts(0)));
+        // The admin flag of the role does not suffice, see `check_role_or_admin`.
+        assert!(!acl.is_authorized_any(Role::L2.into(), &accounts(2)));
+    }
+
+    #[test]
+    fn check_role_or_admin_accepts_grantees_and_admins() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L3, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L3, &accounts(2), &accounts(0));
+
+        acl.check_role_or_admin(Role::L3, &accounts(1));
+        acl.check_role_or_admin(Role::L3, &accounts(2));
+        acl.check_role_or_admin(Role::L3, &accounts(0));
+        // An account which is neither fails the target `check_role_or_admin`
+        // evaluates.
+        let target = AclPermissions::L3 | AclPermissions::L3_ADMIN | AclPermissions::SUPER_ADMIN;
+        assert!(!acl.is_authorized_any(target, &accounts(3)));
+    }
+
+    #[test]
+    fn grant_role_returning_reports_prior_permissions() {
+        let mut acl = setup_acl();
+        assert_eq!(
+            acl.grant_role_returning(Role::L1, &accounts(1)),
+            Some(AclPermissions::empty())
+        );
+        assert_eq!(
+            acl.grant_role_returning(Role::L3, &accounts(1)),
+            Some(AclPermissions::L1)
+        );
+        assert_eq!(
+            acl.get_or_init_permissions(&accounts(1)),
+            AclPermissions::L1 | AclPermissions::L3
+        );
+
+        set_predecessor(accounts(1));
+        assert_eq!(acl.grant_role_returning(Role::L2, &accounts(1)), None);
+    }
+
+    #[test]
+    fn enumeration_is_opt_in() {
+        set_predecessor(accounts(0));
+        let mut plain = Acl::<Role>::with_prefix(b"plain".to_vec(), false);
+        let mut enumerable = Acl::<Role>::with_prefix(b"enumerable".to_vec(), true);
+        for acl in [&mut plain, &mut enumerable] {
+            acl.add_super_admin_unchecked(&accounts(0));
+            acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+            assert!(acl.has_role(Role::L1, &accounts(1)));
+            assert_eq!(acl.get_roles(&accounts(1)), vec![Role::L1]);
+        }
+
+        assert_eq!(
+            plain.export_permissions(0, 10).err(),
+            Some(AclError::EnumerationDisabled)
+        );
+        let requirement = AclRequirement::Any(AclPermissions::L1);
+        assert_eq!(
+            plain.get_authorized_accounts(requirement, 0, 10),
+            Err(AclError::EnumerationDisabled)
+        );
+        let page = enumerable.export_permissions(0, 10).ok().unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(
+            enumerable.get_authorized_accounts(requirement, 0, 10),
+            Ok(vec![accounts(1)])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn events_record_the_block() {
+        let mut acl = setup_acl();
+        testing_env!(get_context(accounts(0))
+            .block_timestamp(1_650_000_000_000_000_000)
+            .block_index(42)
+            .build());
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        let events = logged_events();
+        assert_eq!(events[0]["data"]["block_timestamp"], "1650000000000000000");
+        assert_eq!(events[0]["data"]["block_height"], "42");
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn memos_are_recorded_in_events() {
+        let mut acl = setup_acl();
+        acl.grant_role_with_memo(Role::L1, &accounts(1), "ticket 7".to_string());
+        acl.add_admin_with_memo(Role::L1, &accounts(1), "promotion".to_string());
+        acl.revoke_role_with_memo(Role::L1, &accounts(1), "offboarding".to_string());
+        let memos: Vec<_> = logged_events()
+            .iter()
+            .map(|event| event["data"]["memo"].clone())
+            .collect();
+        assert_eq!(memos, vec!["ticket 7", "promotion", "offboarding"]);
+
+        set_predecessor(accounts(0));
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+        assert!(logged_events()[0]["data"].get("memo").is_none());
+    }
+
+    #[test]
+    fn groups_grant_and_revoke_member_roles() {
+        let mut acl = setup_acl();
+        let editor = "Editor".to_string();
+        assert_eq!(
+            acl.define_group(editor.clone(), &[Role::L1, Role::L2]),
+            Some(true)
+        );
+        assert_eq!(acl.group_roles(&editor), Ok(vec![Role::L1, Role::L2]));
+
+        assert_eq!(acl.grant_group(&editor, &accounts(1)), Ok(Some(true)));
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert!(acl.has_role(Role::L2, &accounts(1)));
+        assert!(!acl.has_role(Role::L3, &accounts(1)));
+
+        assert_eq!(acl.revoke_group(&editor, &accounts(1)), Ok(Some(true)));
+        assert!(acl.get_roles(&accounts(1)).is_empty());
+        assert_eq!(
+            acl.grant_group("Viewer", &accounts(1)),
+            Err(AclError::UnknownGroup {
+                name: "Viewer".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn implications_satisfy_lower_checks() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.set_role_implications(Role::L1, &[Role::L2]), Some(true));
+        assert_eq!(acl.set_role_implications(Role::L2, &[Role::L3]), Some(true));
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+
+        let target = AclPermissions::L2 | AclPermissions::L3;
+        acl.check_all(target, &accounts(1));
+        assert!(acl.has_role(Role::L3, &accounts(1)));
+        assert_eq!(acl.get_roles(&accounts(1)), vec![Role::L1]);
+        assert_eq!(acl.role_implications(Role::L1), vec![Role::L2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Implications of L3 would form a cycle")]
+    fn implications_reject_cycles() {
+        let mut acl = setup_acl();
+        acl.set_role_implications(Role::L1, &[Role::L2]);
+        acl.set_role_implications(Role::L2, &[Role::L3]);
+        acl.set_role_implications(Role::L3, &[Role::L1]);
+    }
+
+    #[test]
+    fn flags_of_the_last_role_fit() {
+        assert_eq!(AclPermissions::MAX_ROLES, 63);
+        let last = AclPermissions::MAX_ROLES - 1;
+        let role_flag = AclPermissions::try_from_shift(last * 2 + 1).unwrap();
+        let admin_flag = AclPermissions::try_from_shift(last * 2 + 2).unwrap();
+        assert_eq!(admin_flag.bits(), 1 << 126);
+        assert_eq!(role_flag.bits(), 1 << 125);
+
+        let beyond = AclPermissions::MAX_ROLES * 2 + 2;
+        assert_eq!(
+            AclPermissions::try_from_shift(beyond),
+            Err(AclError::UndefinedFlag { shift: 128 })
+        );
+    }
+
+    #[test]
+    fn fallible_conversions() {
+        assert_eq!(
+            AclPermissions::try_from_role(Role::L3),
+            Ok(AclPermissions::L3)
+        );
+        assert_eq!(
+            AclPermissions::try_from_admin(AclAdmin::Super),
+            Ok(AclPermissions::SUPER_ADMIN)
+        );
+        assert_eq!(
+            AclPermissions::try_from_admin(AclAdmin::L2),
+            Ok(AclPermissions::L2_ADMIN)
+        );
+
+        // Flags beyond the bitmask and flags missing in the definition.
+        assert_eq!(
+            AclPermissions::try_from_defined_shift(130),
+            Err(AclError::UndefinedFlag { shift: 130 })
+        );
+        assert_eq!(
+            AclPermissions::try_from_defined_shift(7),
+            Err(AclError::UndefinedFlag { shift: 7 })
+        );
+    }
+
+    #[test]
+    fn decode_masks_into_roles() {
+        let mask = AclPermissions::L1 | AclPermissions::L2_ADMIN | AclPermissions::L3;
+        assert_eq!(mask.roles::<Role>(), vec![Role::L1, Role::L3]);
+        assert_eq!(mask.admin_roles::<Role>(), vec![Role::L2]);
+        assert!(!mask.is_super_admin_flag_set());
+
+        let super_admin = AclPermissions::SUPER_ADMIN;
+        assert!(super_admin.roles::<Role>().is_empty());
+        assert!(super_admin.admin_roles::<Role>().is_empty());
+        assert!(super_admin.is_super_admin_flag_set());
+
+        let admin = AclPermissions::L1_ADMIN | AclPermissions::L3_ADMIN;
+        assert_eq!(admin.admin_roles::<Role>(), vec![Role::L1, Role::L3]);
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn emitted_events_are_prefixed() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with(r#"EVENT_JSON:{"standard":"nep297""#));
+    }
+
+    #[test]
+    fn event_serializes_to_json() {
+        testing_env!(get_context(accounts(0))
+            .block_timestamp(1_000)
+            .block_index(7)
+            .build());
+        let account_id = accounts(1);
+        let event = AclEvent::new_from_env("acl_", AclEventId::RoleGranted, Role::L1, &account_id);
+        assert_eq!(
+            event.to_json_event_string(),
+            concat!(
+                r#"{"standard":"nep297","version":"1.0.0","event":"acl_role_granted","#,
+                r#""data":{"role":"L1","account_id":"bob","predecessor":"alice","#,
+                r#""block_timestamp":"1000","block_height":"7"}}"#,
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn event_version_is_configurable() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.event_version(), "1.0.0");
+        assert_eq!(acl.set_event_version(Some("1.1.0".to_string())), Some(true));
+        set_predecessor(accounts(0));
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(logged_events()[0]["version"], "1.1.0");
+
+        assert_eq!(acl.set_event_version(None), Some(true));
+        assert_eq!(acl.event_version(), "1.0.0");
+    }
+
+    #[test]
+    #[should_panic(expected = "Event version 1.1 is not of the form MAJOR.MINOR.PATCH")]
+    fn event_version_must_be_semver() {
+        let mut acl = setup_acl();
+        acl.set_event_version(Some("1.1".to_string()));
+    }
+
+    #[test]
+    fn event_serialization_borrows_accounts() {
+        set_predecessor(accounts(0));
+        let account_id = accounts(2);
+        let event = AclEvent::new_from_env("acl_", AclEventId::AdminAdded, Role::L2, &account_id)
+            .with_context(&AclEventContext::authorized(AclAuthorizedVia::RoleAdmin));
+        // Borrowed fields serialize like owned ones.
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "standard": "nep297",
+                "version": "1.0.0",
+                "event": "acl_admin_added",
+                "data": {
+                    "role": "L2",
+                    "account_id": "charlie",
+                    "predecessor": "alice",
+                    "block_timestamp": "0",
+                    "block_height": "0",
+                    "authorized_via": "RoleAdmin",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn reset_clears_accounts_across_pages() {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<Role>::new_enumerable();
+        acl.add_super_admin_unchecked(&accounts(0));
+        acl.add_admin_unchecked(Role::L1, &accounts(0));
+        for i in 1..6 {
+            acl.grant_role(Role::L1, &accounts(i), &accounts(0));
+        }
+
+        assert_eq!(acl.reset(Some(2)), Ok(Some(true)));
+        assert_eq!(acl.count_grantees(Role::L1), 3);
+        assert_eq!(acl.reset(Some(2)), Ok(Some(true)));
+        assert_eq!(acl.reset(Some(2)), Ok(Some(false)));
+        assert_eq!(acl.count_grantees(Role::L1), 0);
+        // The caller keeps only its super admin flag.
+        assert_eq!(
+            acl.get_or_init_permissions(&accounts(0)),
+            AclPermissions::SUPER_ADMIN
+        );
+        assert_eq!(acl.export_permissions(0, 10).ok().unwrap().items.len(), 1);
+    }
+
+    #[test]
+    fn is_admin_of_any_role_in_a_set() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+
+        assert!(acl.is_admin_of_any(&[Role::L1, Role::L2], &accounts(1)));
+        assert!(!acl.is_admin_of_any(&[Role::L1, Role::L3], &accounts(1)));
+        assert!(!acl.is_admin_of_any(&[], &accounts(1)));
+        // Super admins are admins for any role.
+        assert!(acl.is_admin_of_any(&[], &accounts(0)));
+    }
+
+    #[test]
+    fn role_labels_can_be_overwritten() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.role_label(Role::L1), None);
+        assert_eq!(
+            acl.set_role_label(Role::L1, "Moderator".to_string()),
+            Some(true)
+        );
+        assert_eq!(acl.role_label(Role::L1), Some("Moderator".to_string()));
+
+        assert_eq!(
+            acl.set_role_label(Role::L1, "Moderator".to_string()),
+            Some(false)
+        );
+        assert_eq!(
+            acl.set_role_label(Role::L1, "Editor".to_string()),
+            Some(true)
+        );
+        assert_eq!(acl.role_label(Role::L1), Some("Editor".to_string()));
+        assert_eq!(acl.role_label(Role::L2), None);
+
+        set_predecessor(accounts(1));
+        assert_eq!(acl.set_role_label(Role::L2, "Viewer".to_string()), None);
+    }
+
+    #[test]
+    fn roles_serialize_as_names() {
+        assert_eq!(serde_json::to_string(&Role::L2).unwrap(), r#""L2""#);
+        assert_eq!(serde_json::from_str::<Role>(r#""L3""#).unwrap(), Role::L3);
+        assert!(serde_json::from_str::<Role>("0").is_err());
+    }
+
+    #[test]
+    fn storage_prefixes_are_distinct_and_stable() {
+        let keys = [
+            AclStorageKey::Permissions,
+            AclStorageKey::Bearers,
+            AclStorageKey::BearersSet {
+                permission: AclPermissions::L1,
+            },
+            AclStorageKey::Onboarded,
+            AclStorageKey::MethodRequirements,
+            AclStorageKey::DynamicRoles,
+            AclStorageKey::RoleSchedules,
+            AclStorageKey::Accounts,
+            AclStorageKey::RoleGroups,
+            AclStorageKey::RoleImplications,
+            AclStorageKey::RoleLabels,
+            AclStorageKey::MaxAdmins,
+            AclStorageKey::MinAdmins,
+            AclStorageKey::RoleDenials,
+            AclStorageKey::EventHistory,
+            AclStorageKey::EventHistoryLength,
+            AclStorageKey::DelegatedAdmins,
+            AclStorageKey::EventHistoryCapacity,
+        ];
+        let prefixes: Vec<Vec<u8>> = keys
+            .into_iter()
+            .map(|key| acl_new_storage_prefix(ACL_STORAGE_PREFIX, key))
+            .collect();
+        for (i, a) in prefixes.iter().enumerate() {
+            for b in prefixes.iter().skip(i + 1) {
+                assert!(!a.starts_with(b) && !b.starts_with(a));
+            }
+        }
+        // Keys are the variant index appended to the prefix, so they only
+        // change if variants are reordered.
+        assert_eq!(prefixes[0], b"_acl\x00");
+        assert_eq!(prefixes[13], b"_acl\x0d");
+
+        // Every collection can be created and written to without collisions.
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<Role>::new_enumerable();
+        acl.add_super_admin_unchecked(&accounts(0));
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.set_role_label(Role::L1, "Moderator".to_string());
+        acl.set_max_admins(Role::L1, Some(2));
+        acl.define_group("Editor".to_string(), &[Role::L1, Role::L2]);
+        acl.register_role("L4".to_string());
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert_eq!(acl.count_grantees(Role::L1), 1);
+    }
+
+    #[test]
+    fn cached_permissions_follow_writes() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+
+        // Each call reads the predecessor's permissions, which are cached.
+        assert_eq!(
+            acl.grant_role(Role::L1, &accounts(1), &accounts(1)),
+            Some(true)
+        );
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert_eq!(
+            acl.revoke_admin(Role::L1, &accounts(1), &accounts(1)),
+            Some(true)
+        );
+        // A stale cache would still authorize `accounts(1)`.
+        assert_eq!(acl.grant_role(Role::L1, &accounts(2), &accounts(1)), None);
+        assert_eq!(acl.permissions.get(&accounts(1)), Some(AclPermissions::L1));
+        assert_eq!(
+            acl.permissions_cache.get(&accounts(1)),
+            Some(AclPermissions::L1)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn grant_role_and_admin_sets_both() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.grant_role_and_admin(Role::L3, &accounts(1)), Some(true));
+        assert!(acl.has_role(Role::L3, &accounts(1)));
+        assert!(acl.is_admin(Role::L3, &accounts(1)));
+        assert_eq!(
+            logged_event_names(),
+            vec!["acl_role_granted", "acl_admin_added"]
+        );
+
+        set_predecessor(accounts(2));
+        assert_eq!(acl.grant_role_and_admin(Role::L3, &accounts(3)), None);
+    }
+
+    #[test]
+    fn max_admins_allows_up_to_the_cap() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.set_max_admins(Role::L1, Some(1)), Some(true));
+        assert_eq!(acl.max_admins(Role::L1), Some(1));
+        assert_eq!(
+            acl.add_admin(Role::L1, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        // Re-adding an admin does not count against the cap.
+        assert_eq!(
+            acl.add_admin(Role::L1, &accounts(1), &accounts(0)),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn max_admins_rejects_admins_beyond_the_cap() {
+        let mut acl = setup_acl();
+        acl.set_max_admins(Role::L1, Some(1));
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(
+            acl.try_add_admin(Role::L1, &accounts(2), &accounts(0)),
+            Err(AclError::MaxAdminsReached {
+                role: "L1".to_string(),
+                max: 1,
+            })
+        );
+        assert_eq!(acl.add_admin(Role::L1, &accounts(2), &accounts(0)), None);
+        assert!(!acl.is_admin(Role::L1, &accounts(2)));
+        // Existing admins are not affected by the cap.
+        assert_eq!(
+            acl.add_admin(Role::L1, &accounts(1), &accounts(0)),
+            Some(false)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Role L1 has reached its maximum of 1 admins")]
+    fn max_admins_apply_to_combined_grants() {
+        let mut acl = setup_acl();
+        acl.set_max_admins(Role::L1, Some(1));
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role_and_admin(Role::L1, &accounts(2));
+    }
+
+    #[test]
+    fn min_admins_allows_revoking_above_the_minimum() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L2, &accounts(2), &accounts(0));
+        assert_eq!(acl.set_min_admins(Role::L2, Some(1)), Some(true));
+        assert_eq!(
+            acl.revoke_admin(Role::L2, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert_eq!(acl.count_admins(Role::L2), 1);
+    }
+
+    #[test]
+    fn min_admins_rejects_revoking_the_sole_admin() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        acl.set_min_admins(Role::L2, Some(1));
+        // Super admins are not counted, so `accounts(1)` is the sole admin.
+        assert_eq!(
+            acl.try_revoke_admin(Role::L2, &accounts(1), &accounts(0)),
+            Err(AclError::MinAdminsReached {
+                role: "L2".to_string(),
+                min: 1,
+            })
+        );
+        assert_eq!(acl.revoke_admin(Role::L2, &accounts(1), &accounts(0)), None);
+        assert!(acl.is_admin(Role::L2, &accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Role L2 must keep at least 1 admins")]
+    fn min_admins_rejects_renouncing_the_sole_admin() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        acl.set_min_admins(Role::L2, Some(1));
+        set_predecessor(accounts(1));
+        acl.renounce_admin(Role::L2, true).ok();
+    }
+
+    #[test]
+    #[cfg(feature = "event-history")]
+    fn recent_events_keep_the_latest() {
+        let mut acl = setup_acl();
+        assert_eq!(acl.set_event_history_capacity(2), Some(true));
+        for i in 1..4 {
+            acl.grant_role(Role::L1, &accounts(i), &accounts(0));
+        }
+
+        let events = acl.recent_events(0, 10);
+        let indices: Vec<u64> = events.iter().map(|event| event.index.0).collect();
+        assert_eq!(indices, vec![1, 2]);
+        let logged = logged_events();
+        for (event, logged) in events.iter().zip(&logged[1..]) {
+            let stored: serde_json::Value = serde_json::from_str(&event.json).unwrap();
+            assert_eq!(&stored, logged);
+        }
+        assert_eq!(acl.recent_events(1, 10).len(), 1);
+    }
+
+    #[test]
+    fn diagnose_summarizes_permissions() {
+        let mut acl = setup_acl();
+        let diagnostics = acl.diagnose(&accounts(0));
+        assert!(diagnostics.roles.is_empty());
+        assert!(diagnostics.admin_roles.is_empty());
+        assert!(diagnostics.is_super_admin);
+
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.grant_role(Role::L3, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        let diagnostics = acl.diagnose(&accounts(1));
+        assert_eq!(diagnostics.roles, vec![Role::L1, Role::L3]);
+        assert_eq!(diagnostics.admin_roles, vec![Role::L2]);
+        assert!(!diagnostics.is_super_admin);
+        assert_eq!(
+            serde_json::to_value(&diagnostics).unwrap(),
+            serde_json::json!({
+                "roles": ["L1", "L3"],
+                "admin_roles": ["L2"],
+                "is_super_admin": false,
+            })
+        );
+    }
+
+    #[test]
+    fn swap_role_exchanges_roles() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(acl.swap_role(&accounts(1), Role::L1, Role::L2), Some(true));
+        assert!(!acl.has_role(Role::L1, &accounts(1)));
+        assert!(acl.has_role(Role::L2, &accounts(1)));
+    }
+
+    #[test]
+    fn swap_role_requires_admin_for_both_roles() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L1, &accounts(2), &accounts(0));
+        acl.add_admin(Role::L3, &accounts(3), &accounts(0));
+
+        for admin in [accounts(2), accounts(3)] {
+            set_predecessor(admin);
+            assert_eq!(acl.swap_role(&accounts(1), Role::L1, Role::L3), None);
+            assert!(acl.has_role(Role::L1, &accounts(1)));
+            assert!(!acl.has_role(Role::L3, &accounts(1)));
+        }
+    }
+
+    #[test]
+    fn role_modes_decide_the_default() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        // In allowlist mode only grantees have the role.
+        assert_eq!(acl.role_mode(Role::L1), AclRoleMode::Allowlist);
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert!(!acl.has_role(Role::L1, &accounts(2)));
+
+        assert_eq!(
+            acl.set_role_mode(Role::L1, AclRoleMode::Denylist),
+            Some(true)
+        );
+        // An account without stored permissions passes in denylist mode.
+        assert!(acl.permissions.get(&accounts(2)).is_none());
+        assert!(acl.has_role(Role::L1, &accounts(2)));
+        acl.check_any(AclPermissions::L1, &accounts(2));
+
+        assert_eq!(
+            acl.revoke_role(Role::L1, &accounts(2), &accounts(0)),
+            Some(true)
+        );
+        assert!(!acl.has_role(Role::L1, &accounts(2)));
+        assert!(!acl.is_authorized_any(AclPermissions::L1, &accounts(2)));
+        assert!(acl.has_role(Role::L1, &accounts(3)));
+
+        // Denials have no effect in allowlist mode.
+        acl.set_role_mode(Role::L1, AclRoleMode::Allowlist);
+        assert!(acl.has_role(Role::L1, &accounts(1)));
+        assert!(!acl.has_role(Role::L1, &accounts(3)));
+    }
+
+    #[test]
+    fn assert_helpers_check_the_predecessor() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L2, &accounts(1), &accounts(0));
+        // Super admins pass `assert_admin` for every role.
+        for role in Role::all() {
+            acl.assert_admin(role);
+        }
+        set_predecessor(accounts(1));
+        acl.assert_grantee(Role::L2);
+        assert!(!acl.is_admin(Role::L2, &accounts(1)));
+
+        // Failure messages name the role, preferably by its label.
+        assert_eq!(acl.role_display_name(Role::L1), "L1");
+        set_predecessor(accounts(0));
+        acl.set_role_label(Role::L1, "Moderator".to_string());
+        assert_eq!(acl.role_display_name(Role::L1), "Moderator (L1)");
+    }
+
+    #[test]
+    #[should_panic(expected = "must be an admin for role Moderator (L1)")]
+    fn assert_admin_names_the_labeled_role() {
+        let mut acl = setup_acl();
+        acl.set_role_label(Role::L1, "Moderator".to_string());
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+        acl.assert_admin(Role::L1);
+    }
+
+    #[test]
+    #[should_panic(expected = "must have role Moderator (L1)")]
+    fn assert_grantee_names_the_labeled_role() {
+        let mut acl = setup_acl();
+        acl.set_role_label(Role::L1, "Moderator".to_string());
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+        acl.assert_grantee(Role::L1);
+    }
+
+    #[test]
+    fn revoking_the_last_flag_removes_the_entry() {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<Role>::new_enumerable();
+        acl.add_super_admin_unchecked(&accounts(0));
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        assert!(acl.permissions.get(&accounts(1)).is_some());
+
+        acl.revoke_role(Role::L1, &accounts(1), &accounts(0));
+        assert!(acl.permissions.get(&accounts(1)).is_none());
+        let page = acl.export_permissions(0, 10).ok().unwrap();
+        assert_eq!(page.items, vec![(accounts(0), AclPermissions::SUPER_ADMIN)]);
+        assert!(acl
+            .get_bearers(AclPermissions::L1, 0, 10)
+            .ok()
+            .unwrap()
+            .items
+            .is_empty());
+    }
+
+    #[test]
+    fn grant_permissions_applies_a_mask() {
+        let mut acl = setup_acl();
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        acl.add_admin(Role::L3, &accounts(1), &accounts(0));
+        set_predecessor(accounts(1));
+
+        let mask = AclPermissions::L1 | AclPermissions::L3;
+        assert_eq!(acl.grant_permissions(&accounts(2), mask), Some(true));
+        assert_eq!(acl.get_or_init_permissions(&accounts(2)), mask);
+        assert_eq!(acl.grant_permissions(&accounts(2), mask), Some(false));
+
+        // Lacking admin permissions for L2 rejects the whole mask.
+        let mask = AclPermissions::L1 | AclPermissions::L2;
+        assert_eq!(acl.grant_permissions(&accounts(3), mask), None);
+        assert!(acl.get_or_init_permissions(&accounts(3)).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "events")]
+    fn repeated_grants_emit_one_event() {
+        let mut acl = setup_acl();
+        for _ in 0..3 {
+            acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+        }
+        acl.grant_role_to_many(Role::L1, &[accounts(1), accounts(1)]);
+        acl.grant_permissions(&accounts(1), AclPermissions::L1);
+        acl.import_permissions(vec![(accounts(1), AclPermissions::L1.bits())]);
+        acl.grant_role_by_name("L1", &accounts(1)).ok();
+        assert_eq!(logged_event_names(), vec!["acl_role_granted"]);
+    }
+
+    #[test]
+    fn would_change_reflects_current_grants() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(1), &accounts(0));
+
+        // A holder cannot be granted the role again, only revoked.
+        assert!(!acl.would_grant_change(Role::L1, &accounts(1)));
+        assert!(acl.would_revoke_change(Role::L1, &accounts(1)));
+        // It is the other way around for an account without the role.
+        assert!(acl.would_grant_change(Role::L1, &accounts(2)));
+        assert!(!acl.would_revoke_change(Role::L1, &accounts(2)));
+
+        // The dry runs agree with the actual mutations.
+        assert_eq!(
+            acl.grant_role(Role::L1, &accounts(1), &accounts(0)),
+            Some(false)
+        );
+        assert_eq!(
+            acl.revoke_role(Role::L1, &accounts(2), &accounts(0)),
+            Some(false)
+        );
+        assert_eq!(
+            acl.revoke_role(Role::L1, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert!(acl.would_grant_change(Role::L1, &accounts(1)));
+    }
+
+    #[test]
+    fn delegated_admins_grant_but_do_not_add_admins() {
+        let mut acl = setup_acl();
+        assert_eq!(
+            acl.set_delegated_admin(Role::L2, &accounts(1), true),
+            Some(true)
+        );
+        assert!(acl.is_delegated_admin(Role::L2, &accounts(1)));
+        assert!(!acl.is_admin(Role::L2, &accounts(1)));
+
+        set_predecessor(accounts(1));
+        assert_eq!(
+            acl.grant_role(Role::L2, &accounts(2), &accounts(1)),
+            Some(true)
+        );
+        assert!(acl.has_role(Role::L2, &accounts(2)));
+        assert_eq!(acl.add_admin(Role::L2, &accounts(2), &accounts(1)), None);
+        assert_eq!(
+            acl.try_add_admin(Role::L2, &accounts(2), &accounts(1)),
+            Err(AclError::NotAdmin {
+                role: "L2".to_string(),
+                predecessor: accounts(1),
+            })
+        );
+        assert!(!acl.is_admin(Role::L2, &accounts(2)));
+        // Delegation is scoped to a role and does not allow revoking it.
+        assert_eq!(acl.grant_role(Role::L1, &accounts(2), &accounts(1)), None);
+        assert_eq!(acl.revoke_role(Role::L2, &accounts(2), &accounts(1)), None);
+
+        // Only admins may delegate.
+        assert_eq!(acl.set_delegated_admin(Role::L2, &accounts(3), true), None);
+    }
+
+    #[test]
+    fn admin_roles_expand_super_admins() {
+        let mut acl = setup_acl();
+        assert_eq!(
+            acl.get_admin_roles(&accounts(0)),
+            vec![Role::L1, Role::L2, Role::L3]
+        );
+        // Decoding the flags of the super admin yields no roles.
+        assert!(acl
+            .get_or_init_permissions(&accounts(0))
+            .admin_roles::<Role>()
+            .is_empty());
+
+        acl.add_admin(Role::L2, &accounts(1), &accounts(0));
+        assert_eq!(acl.get_admin_roles(&accounts(1)), vec![Role::L2]);
+        assert!(acl.get_admin_roles(&accounts(2)).is_empty());
+    }
+
+    #[test]
+    fn unknown_accounts_pass_denylist_checks() {
+        let mut acl = setup_acl();
+        acl.set_role_mode(Role::L2, AclRoleMode::Denylist);
+        let unknown = accounts(3);
+        assert!(acl.permissions.get(&unknown).is_none());
+
+        acl.check_role(Role::L2, &unknown);
+        acl.check_all(AclPermissions::L2, &unknown);
+        assert!(acl
+            .effective_permissions(&unknown)
+            .contains(AclPermissions::L2));
+        // Roles in allowlist mode still have to be granted.
+        assert_eq!(
+            acl.try_check_all(AclPermissions::L1 | AclPermissions::L2, &unknown),
+            Err(AclError::MissingAllPermissions {
+                account_id: unknown.clone(),
+                missing: AclPermissions::L1,
+                required: AclPermissions::L1 | AclPermissions::L2,
+            })
+        );
+        assert!(acl.is_authorized_any(AclPermissions::L1 | AclPermissions::L2, &unknown));
+    }
+
+    #[test]
+    fn try_variants_report_not_admin() {
+        let mut acl = setup_acl();
+        acl.grant_role(Role::L1, &accounts(2), &accounts(0));
+        acl.add_admin(Role::L1, &accounts(2), &accounts(0));
+
+        set_predecessor(accounts(1));
+        let not_admin = || AclError::NotAdmin {
+            role: "L1".to_string(),
+            predecessor: accounts(1),
+        };
+        assert_eq!(
+            acl.try_add_admin(Role::L1, &accounts(3), &accounts(1)),
+            Err(not_admin())
+        );
+        assert_eq!(
+            acl.try_revoke_admin(Role::L1, &accounts(2), &accounts(1)),
+            Err(not_admin())
+        );
+        assert_eq!(
+            acl.try_grant_role(Role::L1, &accounts(3), &accounts(1)),
+            Err(not_admin())
+        );
+        assert_eq!(
+            acl.try_revoke_role(Role::L1, &accounts(2), &accounts(1)),
+            Err(not_admin())
+        );
+        // The `Option` methods map the error to `None`.
+        assert_eq!(acl.add_admin(Role::L1, &accounts(3), &accounts(1)), None);
+        assert_eq!(acl.revoke_admin(Role::L1, &accounts(2), &accounts(1)), None);
+        assert_eq!(acl.grant_role(Role::L1, &accounts(3), &accounts(1)), None);
+        assert_eq!(acl.revoke_role(Role::L1, &accounts(2), &accounts(1)), None);
+        assert!(acl.has_role(Role::L1, &accounts(2)));
+        assert!(acl.is_admin(Role::L1, &accounts(2)));
+
+        set_predecessor(accounts(0));
+        assert_eq!(
+            acl.try_grant_role(Role::L1, &accounts(3), &accounts(0)),
+            Ok(true)
+        );
+        assert_eq!(
+            acl.try_grant_role(Role::L1, &accounts(3), &accounts(0)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn try_revoke_admin_reports_super_admins() {
+        let mut acl = setup_acl();
+        acl.add_super_admin(&accounts(1));
+        acl.add_admin(Role::L1, &accounts(1), &accounts(0));
+        assert_eq!(
+            acl.try_revoke_admin(Role::L1, &accounts(1), &accounts(0)),
+            Err(AclError::RedundantRevoke {
+                account_id: accounts(1),
+                role: "L1".to_string(),
+            })
+        );
+        assert_eq!(acl.revoke_admin(Role::L1, &accounts(1), &accounts(0)), None);
+        assert!(acl
+            .get_or_init_permissions(&accounts(1))
+            .contains(AclPermissions::L1_ADMIN));
+    }
+
+    #[test]
+    fn pages_report_has_more() {
+        set_predecessor(accounts(0));
+        let mut acl = Acl::<Role>::new_enumerable();
+        acl.add_super_admin_unchecked(&accounts(0));
+        let grantees: Vec<AccountId> = (0..ACL_MAX_PAGE_SIZE + 20)
+            .map(|i| format!("user{}.near", i).parse().unwrap())
+            .collect();
+        for account_id in grantees.iter() {
+            // Start a new context to stay below the limit of the log length.
+            set_predecessor(accounts(0));
+            acl.grant_role(Role::L1, account_id, &accounts(0));
+        }
+
+        let mut bearers = vec![];
+        let mut pages = 0;
+        loop {
+            let page = acl
+                .get_bearers(AclPermissions::L1, bearers.len(), ACL_MAX_PAGE_SIZE)
+                .ok()
+                .unwrap();
+            pages += 1;
+            bearers.extend(page.items);
+            if !page.has_more {
+                break;
+            }
+        }
+        assert_eq!(pages, 2);
+        assert_eq!(bearers, grantees);
+
+        // The super admin is exported, too. A page ending at the last account
+        // does not report more.
+        let page = acl
+            .export_permissions(grantees.len() - 20, 21)
+            .ok()
+            .unwrap();
+        assert_eq!(page.items.len(), 21);
+        assert!(!page.has_more);
+        let page = acl.export_permissions(0, 20).ok().unwrap();
+        assert!(page.has_more);
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 100 items can be requested per page")]
+    fn pages_are_capped() {
+        let acl = setup_acl();
+        acl.get_bearers(AclPermissions::L1, 0, ACL_MAX_PAGE_SIZE + 1)
+            .ok();
+    }
+
+    /// Returns an `Acl` without super admin in which `accounts(1)` is the only
+    /// admin for `Role::L1` and the predecessor.
+    fn setup_sole_admin() -> Acl<Role> {
+        set_predecessor(accounts(1));
+        let mut acl = Acl::new();
+        acl.add_admin_unchecked(Role::L1, &accounts(1));
+        acl
+    }
+
+    #[test]
+    #[should_panic(expected = "Caller is the last admin for L1, pass force to renounce anyway")]
+    fn renouncing_the_last_admin_requires_force() {
+        let mut acl = setup_sole_admin();
+        acl.renounce_admin(Role::L1, false).ok();
+    }
+
+    #[test]
+    fn renouncing_the_last_admin_with_force() {
+        let mut acl = setup_sole_admin();
+        assert_eq!(acl.renounce_admin(Role::L1, true), Ok(true));
+        assert!(!acl.is_admin(Role::L1, &accounts(1)));
+        assert_eq!(acl.count_admins(Role::L1), 0);
+    }
+
+    #[test]
+    fn renouncing_is_unforced_with_other_admins() {
+        let mut acl = setup_sole_admin();
+        acl.add_admin(Role::L1, &accounts(2), &accounts(1));
+        assert_eq!(acl.renounce_admin(Role::L1, false), Ok(true));
+    }
+
+    #[test]
+    fn renouncing_is_unforced_with_a_super_admin() {
+        // The super admin can still administer the role.
+        let mut acl = setup_sole_admin();
+        acl.add_super_admin_unchecked(&accounts(0));
+        set_predecessor(accounts(1));
+        assert_eq!(acl.renounce_admin(Role::L1, false), Ok(true));
+        // Renouncing a role one is no admin for does not need force.
+        assert_eq!(acl.renounce_admin(Role::L2, false), Ok(false));
+    }
+
+    // The checked methods take the predecessor as a parameter, hence the tests
+    // below run without setting up a VM context via `testing_env!`.
+
+    fn not_admin(role: &str, predecessor: &AccountId) -> AclError {
+        AclError::NotAdmin {
+            role: role.to_string(),
+            predecessor: predecessor.clone(),
+        }
+    }
+
+    #[test]
+    fn role_admins_pass_checks_only_for_their_role() {
+        let (admin, grantee) = (accounts(1), accounts(2));
+        let mut acl = Acl::<Role>::new();
+        acl.add_admin_unchecked(Role::L1, &admin);
+
+        assert_eq!(acl.try_grant_role(Role::L1, &grantee, &admin), Ok(true));
+        assert_eq!(acl.try_add_admin(Role::L1, &grantee, &admin), Ok(true));
+        assert_eq!(acl.try_revoke_admin(Role::L1, &grantee, &admin), Ok(true));
+        assert_eq!(acl.try_revoke_role(Role::L1, &grantee, &admin), Ok(true));
+        assert_eq!(
+            acl.try_grant_role(Role::L2, &grantee, &admin),
+            Err(not_admin("L2", &admin))
+        );
+        assert_eq!(
+            acl.try_add_admin(Role::L2, &grantee, &admin),
+            Err(not_admin("L2", &admin))
+        );
+    }
+
+    #[test]
+    fn accounts_without_admin_flags_fail_checks() {
+        let (admin, other) = (accounts(1), accounts(3));
+        let mut acl = Acl::<Role>::new();
+        acl.add_admin_unchecked(Role::L1, &admin);
+        acl.grant_role_unchecked(Role::L1, &other);
+
+        // Holding a role does not allow to administer it.
+        assert_eq!(
+            acl.try_grant_role(Role::L1, &accounts(2), &other),
+            Err(not_admin("L1", &other))
+        );
+        assert_eq!(
+            acl.try_revoke_role(Role::L1, &other, &other),
+            Err(not_admin("L1", &other))
+        );
+        assert_eq!(
+            acl.try_revoke_admin(Role::L1, &admin, &other),
+            Err(not_admin("L1", &other))
+        );
+        assert_eq!(acl.revoke_admin(Role::L1, &admin, &other), None);
+        assert!(acl.is_admin(Role::L1, &admin));
+    }
+
+    #[test]
+    fn super_admins_pass_checks_for_every_role() {
+        let super_admin = accounts(0);
+        let mut acl = Acl::<Role>::new();
+        acl.add_super_admin_unchecked(&super_admin);
+        for role in Role::all() {
+            assert_eq!(acl.add_admin(role, &accounts(1), &super_admin), Some(true));
+            assert_eq!(acl.grant_role(role, &accounts(2), &super_admin), Some(true));
+            assert_eq!(
+                acl.revoke_role(role, &accounts(2), &super_admin),
+                Some(true)
+            );
+        }
+    }
+}
@@ -1,49 +1,175 @@
-// TODO
-// - add enumeration; should it be opt-in or opt-out?
-// - how to assign `AclAdmin::Super`?
-//   - auto-assign to caller of `new` or let developer assign it to accounts?
-// - Consider `AclAdmin::Super` before emitting events?
-//   - Assume alice.near has `AclPermissons::SUPER_ADMIN | AclPermissions::L1_ADMIN`.
-//     When flag L1_ADMIN is removed, alice.near effectively remains admin for
-//     L1 via SUPER_ADMIN.
-
-use bitflags::bitflags;
+mod acl;
+#[cfg(test)]
+mod test_utils;
+
+use acl::AclV0;
+#[cfg(feature = "event-history")]
+pub use acl::StoredAclEvent;
+pub use acl::{
+    Acl, AclAdmin, AclAuthorizedVia, AclDiagnostics, AclError, AclEvent, AclEventId,
+    AclEventMetadata, AclGrantSchedule, AclPage, AclPermissions, AclRequirement, AclRole,
+    AclRoleMode, PendingOp, Role, SuperAdminProposal,
+};
+
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{UnorderedMap, UnorderedSet};
-use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::json_types::{U128, U64};
 use near_sdk::serde_json;
-use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
-
-/// Roles are represented by enum variants.
-#[derive(Copy, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
-#[serde(crate = "near_sdk::serde")]
-#[repr(u8)]
-pub enum Role {
-    L1,
-    L2,
-    L3,
+use near_sdk::{
+    env, ext_contract, near_bindgen, require, AccountId, Gas, PanicOnDefault, Promise, PromiseError,
+};
+use std::collections::BTreeMap;
+
+/// Interface to query the ACL of another contract, e.g.
+/// `ext_acl::ext(acl_contract).acl_has_role(role, account_id)`.
+#[ext_contract(ext_acl)]
+pub trait ExtAcl {
+    fn acl_is_admin(&self, role: Role, account_id: AccountId) -> bool;
+    fn acl_has_role(&self, role: Role, account_id: AccountId) -> bool;
+    fn acl_is_super_admin(&self, account_id: AccountId) -> bool;
+}
+
+/// Gas attached to a view of a remote ACL.
+const GAS_FOR_REMOTE_ACL_VIEW: Gas = Gas(5_000_000_000_000);
+/// Gas attached to the callback handling the result of a remote ACL view.
+const GAS_FOR_ON_REMOTE_HAS_ROLE: Gas = Gas(5_000_000_000_000);
+
+/// Storage key of the version of the layout of the contract state.
+const STATE_VERSION_KEY: &[u8] = b"state_version";
+/// Version of the current layout of `Counter`. When changing the layout of a
+/// deployed version, bump it and extend `Counter::migrate` to convert the
+/// previous layout.
+const STATE_VERSION: u8 = 1;
+
+/// Layout of `Counter` before the state version was tracked.
+#[derive(BorshDeserialize)]
+struct CounterV0 {
+    counter: u64,
+    acl: AclV0,
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Counter {
     counter: u64,
-    acl: Acl,
+    acl: Acl<Role>,
+    /// If set, methods that modify the counter fail.
+    paused: bool,
 }
 
 #[near_bindgen]
 impl Counter {
     #[init]
     pub fn new() -> Self {
+        Self::new_with_admin(&env::predecessor_account_id())
+    }
+
+    /// Like `new`, but additionally makes `super_admin` a super admin, who is
+    /// admin for every `Role` and may e.g. pause the contract.
+    #[init]
+    pub fn new_with_super_admin(super_admin: AccountId) -> Self {
+        let mut contract = Self::new_with_admin(&env::predecessor_account_id());
+        contract.acl.add_super_admin_unchecked(&super_admin);
+        contract
+    }
+
+    /// Like `new_with_super_admin`, but seeds `genesis` instead of the deployer
+    /// as initial admin and makes it super admin. This allows e.g. a DAO to be
+    /// the genesis admin of a contract deployed from another key, which is left
+    /// without permissions.
+    ///
+    /// The validity of `genesis` is ensured when deserializing the `AccountId`.
+    #[init]
+    pub fn new_with_genesis_admin(genesis: AccountId) -> Self {
+        let mut contract = Self::new_with_admin(&genesis);
+        contract.acl.add_super_admin_unchecked(&genesis);
+        contract
+    }
+
+    /// Panics if `bits` contain flags not defined in `AclPermissions`.
+    fn parse_permissions(bits: U128) -> AclPermissions {
+        AclPermissions::from_bits(bits.0).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "Permissions {:#x} contain undefined flags",
+                bits.0
+            ))
+        })
+    }
+
+    /// Converts a `u64` argument to `usize`, saturating at `usize::MAX` on
+    /// targets like wasm32 where `usize` has fewer bits. A saturated `skip`
+    /// yields an empty page instead of wrapping around to an earlier one.
+    fn to_usize(value: u64) -> usize {
+        usize::try_from(value).unwrap_or(usize::MAX)
+    }
+
+    /// Converts the state written by a previous version of the contract to
+    /// the current layout. Must be called after deploying code which changes
+    /// the layout. Repeated calls leave the state unchanged.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = match env::storage_read(STATE_VERSION_KEY) {
+            Some(bytes) => match bytes.as_slice() {
+                [version] => *version,
+                _ => env::panic_str("Failed to read state version"),
+            },
+            // The version was not tracked initially.
+            None => 0,
+        };
+        let contract: Self = match version {
+            0 => Self::from_v0(Self::read_state()),
+            STATE_VERSION => Self::read_state(),
+            _ => env::panic_str(&format!("Unknown state version {}", version)),
+        };
+        Self::write_state_version();
+        contract
+    }
+
+    /// Reads the contract state, which is expected to have layout `T`.
+    fn read_state<T: BorshDeserialize>() -> T {
+        env::state_read().unwrap_or_else(|| env::panic_str("Contract state is missing"))
+    }
+
+    /// Converts the state of version 0.
+    fn from_v0(old: CounterV0) -> Self {
+        Self {
+            counter: old.counter,
+            acl: old.acl.into(),
+            paused: false,
+        }
+    }
+
+    /// Records that the state has the current layout.
+    fn write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &[STATE_VERSION]);
+    }
+
+    /// Initializes the contract with `admin` as admin for every `Role`.
+    fn new_with_admin(admin: &AccountId) -> Self {
         let mut contract = Self {
             counter: 0,
-            acl: Acl::new(),
+            acl: Acl::new_enumerable(),
+            paused: false,
         };
+        Self::write_state_version();
 
-        let caller = env::predecessor_account_id();
-        contract.acl.add_admin_unchecked(Role::L1, &caller);
-        contract.acl.add_admin_unchecked(Role::L2, &caller);
-        contract.acl.add_admin_unchecked(Role::L3, &caller);
+        contract.acl.add_admin_unchecked(Role::L1, admin);
+        contract.acl.add_admin_unchecked(Role::L2, admin);
+        contract.acl.add_admin_unchecked(Role::L3, admin);
+
+        // Requirements of methods decorated with ACL attributes. Super admins
+        // may adjust them later on.
+        contract
+            .acl
+            .set_method_requirement_unchecked("foo2", AclRequirement::Any(AclPermissions::L2));
+        contract.acl.set_method_requirement_unchecked(
+            "foo3",
+            AclRequirement::Any(AclPermissions::L1 | AclPermissions::L2),
+        );
+        contract.acl.set_method_requirement_unchecked(
+            "foo4",
+            AclRequirement::All(AclPermissions::L1 | AclPermissions::L3),
+        );
 
         contract
     }
@@ -52,497 +178,1153 @@ impl Counter {
 
     pub fn foo2(&self) {
         self.acl
-            .check_any(AclPermissions::L2, &env::predecessor_account_id());
+            .check_method("foo2", &env::predecessor_account_id());
     }
 
     pub fn foo3(&self) {
-        self.acl.check_any(
-            AclPermissions::L1 | AclPermissions::L2,
-            &env::predecessor_account_id(),
-        );
+        self.acl
+            .check_method("foo3", &env::predecessor_account_id());
     }
 
     pub fn foo4(&self) {
-        self.acl.check_all(
-            AclPermissions::L1 | AclPermissions::L3,
-            &env::predecessor_account_id(),
+        self.acl
+            .check_method("foo4", &env::predecessor_account_id());
+    }
+
+    /// Increments the counter. Requires the predecessor to have `Role::L1` or to
+    /// be a super admin. Fails while the contract is paused.
+    pub fn increment(&mut self) {
+        self.assert_not_paused();
+        require!(
+            self.acl
+                .has_role_or_super(Role::L1, &env::predecessor_account_id()),
+            "Only L1 grantees can increment the counter",
+        );
+        self.counter += 1;
+    }
+
+    /// Resets the counter to zero. Requires the predecessor to be a super
+    /// admin.
+    pub fn reset(&mut self) {
+        self.assert_super_admin();
+        self.counter = 0;
+    }
+
+    pub fn get_counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Pauses the contract, making `increment` fail until it is unpaused.
+    /// Requires the predecessor to be a super admin. Returns whether the
+    /// contract was unpaused before.
+    pub fn pause(&mut self) -> bool {
+        self.assert_super_admin();
+        let was_unpaused = !self.paused;
+        self.paused = true;
+        was_unpaused
+    }
+
+    /// Unpauses the contract. Requires the predecessor to be a super admin.
+    /// Returns whether the contract was paused before.
+    pub fn unpause(&mut self) -> bool {
+        self.assert_super_admin();
+        let was_paused = self.paused;
+        self.paused = false;
+        was_paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn assert_not_paused(&self) {
+        require!(!self.paused, "Contract is paused");
+    }
+
+    fn assert_super_admin(&self) {
+        require!(
+            self.acl.is_super_admin(&env::predecessor_account_id()),
+            "Only super admins can call this method",
         );
     }
 
+    /// Entry method for new members. The first call of an account grants it
+    /// the role configured via `acl_set_auto_grant_role`, if any. Returns
+    /// whether the role was granted.
+    pub fn join(&mut self) -> bool {
+        self.acl.onboard(&env::predecessor_account_id())
+    }
+
+    /// Example of querying the ACL of another contract. Asks `acl_contract`
+    /// whether `account_id` has `role` and resolves to the answer, or to
+    /// `false` if the remote call fails.
+    pub fn remote_has_role(
+        &self,
+        acl_contract: AccountId,
+        role: Role,
+        account_id: AccountId,
+    ) -> Promise {
+        ext_acl::ext(acl_contract)
+            .with_static_gas(GAS_FOR_REMOTE_ACL_VIEW)
+            .acl_has_role(role, account_id)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_REMOTE_HAS_ROLE)
+                    .on_remote_has_role(),
+            )
+    }
+
+    #[private]
+    pub fn on_remote_has_role(
+        &self,
+        #[callback_result] has_role: Result<bool, PromiseError>,
+    ) -> bool {
+        has_role.unwrap_or(false)
+    }
+
     // Some ACL methods should be automatically exposed on the contract:
 
     pub fn acl_is_admin(&self, role: Role, account_id: &AccountId) -> bool {
         self.acl.is_admin(role, account_id)
     }
 
+    pub fn acl_is_admin_of_any(&self, roles: Vec<Role>, account_id: &AccountId) -> bool {
+        self.acl.is_admin_of_any(&roles, account_id)
+    }
+
     pub fn acl_add_admin(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        self.acl.add_admin(role, account_id)
+        self.acl
+            .add_admin(role, account_id, &env::predecessor_account_id())
+    }
+
+    #[handle_result]
+    pub fn acl_try_add_admin(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+    ) -> Result<bool, AclError> {
+        self.acl
+            .try_add_admin(role, account_id, &env::predecessor_account_id())
+    }
+
+    pub fn acl_add_admin_with_memo(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        memo: String,
+    ) -> Option<bool> {
+        self.acl.add_admin_with_memo(role, account_id, memo)
+    }
+
+    pub fn acl_set_delegated_admin(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        delegated: bool,
+    ) -> Option<bool> {
+        self.acl.set_delegated_admin(role, account_id, delegated)
+    }
+
+    pub fn acl_is_delegated_admin(&self, role: Role, account_id: &AccountId) -> bool {
+        self.acl.is_delegated_admin(role, account_id)
+    }
+
+    pub fn acl_set_max_admins(&mut self, role: Role, max: Option<u32>) -> Option<bool> {
+        self.acl.set_max_admins(role, max)
+    }
+
+    pub fn acl_max_admins(&self, role: Role) -> Option<u32> {
+        self.acl.max_admins(role)
+    }
+
+    pub fn acl_set_min_admins(&mut self, role: Role, min: Option<u32>) -> Option<bool> {
+        self.acl.set_min_admins(role, min)
+    }
+
+    pub fn acl_min_admins(&self, role: Role) -> Option<u32> {
+        self.acl.min_admins(role)
     }
 
     pub fn acl_revoke_admin(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        self.acl.revoke_admin(role, account_id)
+        self.acl
+            .revoke_admin(role, account_id, &env::predecessor_account_id())
+    }
+
+    #[handle_result]
+    pub fn acl_try_revoke_admin(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+    ) -> Result<bool, AclError> {
+        self.acl
+            .try_revoke_admin(role, account_id, &env::predecessor_account_id())
+    }
+
+    pub fn acl_transfer_admin(
+        &mut self,
+        role: Role,
+        from: AccountId,
+        to: AccountId,
+    ) -> Option<bool> {
+        self.acl.transfer_admin(role, &from, &to)
+    }
+
+    #[handle_result]
+    pub fn acl_renounce_admin(&mut self, role: Role, force: bool) -> Result<bool, AclError> {
+        self.acl.renounce_admin(role, force)
+    }
+
+    pub fn acl_is_super_admin(&self, account_id: &AccountId) -> bool {
+        self.acl.is_super_admin(account_id)
+    }
+
+    pub fn acl_add_super_admin(&mut self, account_id: &AccountId) -> Option<bool> {
+        self.acl.add_super_admin(account_id)
+    }
+
+    pub fn acl_revoke_super_admin(&mut self, account_id: &AccountId) -> Option<bool> {
+        self.acl.revoke_super_admin(account_id)
+    }
+
+    pub fn acl_propose_super_admin(&mut self, new_admin: AccountId) -> Option<bool> {
+        self.acl.propose_super_admin(new_admin)
+    }
+
+    pub fn acl_accept_super_admin(&mut self) -> bool {
+        self.acl.accept_super_admin()
+    }
+
+    pub fn acl_renounce_super_admin(&mut self) -> bool {
+        self.acl.renounce_super_admin()
+    }
+
+    pub fn acl_import_permissions(&mut self, entries: Vec<(AccountId, U128)>) -> Option<bool> {
+        let entries = entries
+            .into_iter()
+            .map(|(account_id, permissions)| (account_id, permissions.0))
+            .collect();
+        self.acl.import_permissions(entries)
+    }
+
+    pub fn acl_revoke_all(&mut self, account_id: &AccountId) -> Option<bool> {
+        self.acl.revoke_all(account_id)
+    }
+
+    #[handle_result]
+    pub fn acl_reset(&mut self, limit: Option<u64>) -> Result<Option<bool>, AclError> {
+        self.acl.reset(limit.map(Self::to_usize))
+    }
+
+    pub fn acl_has_super_admin(&self) -> bool {
+        self.acl.has_super_admin()
+    }
+
+    pub fn acl_get_permissions(&self, account_id: &AccountId) -> U128 {
+        U128(self.acl.get_permission_bits(account_id))
+    }
+
+    pub fn acl_would_grant_change(&self, role: Role, account_id: &AccountId) -> bool {
+        self.acl.would_grant_change(role, account_id)
     }
 
-    pub fn acl_renounce_admin(&mut self, role: Role) -> bool {
-        self.acl.renounce_admin(role)
+    pub fn acl_would_revoke_change(&self, role: Role, account_id: &AccountId) -> bool {
+        self.acl.would_revoke_change(role, account_id)
+    }
+
+    pub fn acl_diagnose(&self, account_id: &AccountId) -> AclDiagnostics<Role> {
+        self.acl.diagnose(account_id)
+    }
+
+    pub fn acl_effective_permissions(&self, account_id: &AccountId) -> U128 {
+        U128(self.acl.effective_permissions(account_id).bits())
+    }
+
+    pub fn acl_get_flags(&self, account_id: &AccountId) -> BTreeMap<String, bool> {
+        self.acl.get_flags(account_id)
+    }
+
+    pub fn acl_storage_bytes(&self) -> u64 {
+        self.acl.storage_bytes()
+    }
+
+    pub fn acl_storage_cost(&self) -> U128 {
+        U128(self.acl.storage_bytes() as u128 * env::storage_byte_cost())
+    }
+
+    pub fn acl_check_batch(
+        &self,
+        target_roles: Vec<Role>,
+        require_all: bool,
+        account_ids: Vec<AccountId>,
+    ) -> Vec<bool> {
+        let requirement = AclRequirement::from_roles(&target_roles, require_all);
+        self.acl.check_batch(requirement, &account_ids)
+    }
+
+    pub fn acl_pending_operations(&self) -> Vec<PendingOp> {
+        self.acl.pending_operations()
     }
 
     pub fn acl_has_role(&self, role: Role, account_id: &AccountId) -> bool {
         self.acl.has_role(role, account_id)
     }
 
+    pub fn acl_is_authorized_any(&self, target: U128, account_id: &AccountId) -> bool {
+        self.acl
+            .is_authorized_any(Self::parse_permissions(target), account_id)
+    }
+
+    pub fn acl_is_authorized_all(&self, target: U128, account_id: &AccountId) -> bool {
+        self.acl
+            .is_authorized_all(Self::parse_permissions(target), account_id)
+    }
+
+    pub fn acl_has_role_or_super(&self, role: Role, account_id: &AccountId) -> bool {
+        self.acl.has_role_or_super(role, account_id)
+    }
+
+    pub fn acl_has_any_role(&self, roles: Vec<Role>, account_id: &AccountId) -> bool {
+        self.acl.has_any_role(&roles, account_id)
+    }
+
+    pub fn acl_has_all_roles(&self, roles: Vec<Role>, account_id: &AccountId) -> bool {
+        self.acl.has_all_roles(&roles, account_id)
+    }
+
+    pub fn acl_get_roles(&self, account_id: &AccountId) -> Vec<Role> {
+        self.acl.get_roles(account_id)
+    }
+
+    pub fn acl_admin_roles(&self, account_id: &AccountId) -> Vec<Role> {
+        self.acl.get_admin_roles(account_id)
+    }
+
     pub fn acl_grant_role(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        self.acl.grant_role(role, account_id)
+        self.acl
+            .grant_role(role, account_id, &env::predecessor_account_id())
+    }
+
+    #[handle_result]
+    pub fn acl_try_grant_role(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+    ) -> Result<bool, AclError> {
+        self.acl
+            .try_grant_role(role, account_id, &env::predecessor_account_id())
+    }
+
+    pub fn acl_grant_role_returning(&mut self, role: Role, account_id: &AccountId) -> Option<U128> {
+        self.acl
+            .grant_role_returning(role, account_id)
+            .map(|permissions| U128(permissions.bits()))
+    }
+
+    pub fn acl_grant_role_to_many(
+        &mut self,
+        role: Role,
+        account_ids: Vec<AccountId>,
+    ) -> Option<Vec<bool>> {
+        self.acl.grant_role_to_many(role, &account_ids)
+    }
+
+    pub fn acl_grant_permissions(&mut self, account_id: &AccountId, mask: U128) -> Option<bool> {
+        self.acl
+            .grant_permissions(account_id, Self::parse_permissions(mask))
+    }
+
+    pub fn acl_grant_role_with_extra(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        extra: serde_json::Value,
+    ) -> Option<bool> {
+        self.acl.grant_role_with_extra(role, account_id, extra)
+    }
+
+    pub fn acl_grant_role_with_memo(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        memo: String,
+    ) -> Option<bool> {
+        self.acl.grant_role_with_memo(role, account_id, memo)
+    }
+
+    pub fn acl_grant_role_until(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        expires_at: U64,
+    ) -> Option<bool> {
+        self.acl.grant_role_until(role, account_id, expires_at.0)
+    }
+
+    pub fn acl_grant_role_from(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        effective_at: U64,
+    ) -> Option<bool> {
+        self.acl.grant_role_from(role, account_id, effective_at.0)
+    }
+
+    pub fn acl_role_schedule(
+        &self,
+        role: Role,
+        account_id: &AccountId,
+    ) -> Option<AclGrantSchedule> {
+        self.acl.role_schedule(role, account_id)
+    }
+
+    pub fn acl_grant_role_and_admin(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
+        self.acl.grant_role_and_admin(role, account_id)
+    }
+
+    /// Makes `account_id` a grantee and an admin of `role` in one call, given
+    /// that the predecessor is an admin for `role`. Shorthand for
+    /// `acl_grant_role_and_admin` when onboarding a role holder.
+    pub fn acl_init_role_holder(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
+        self.acl.grant_role_and_admin(role, account_id)
     }
 
     pub fn acl_revoke_role(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        self.acl.revoke_role(role, account_id)
+        self.acl
+            .revoke_role(role, account_id, &env::predecessor_account_id())
+    }
+
+    #[handle_result]
+    pub fn acl_try_revoke_role(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+    ) -> Result<bool, AclError> {
+        self.acl
+            .try_revoke_role(role, account_id, &env::predecessor_account_id())
+    }
+
+    pub fn acl_revoke_role_with_memo(
+        &mut self,
+        role: Role,
+        account_id: &AccountId,
+        memo: String,
+    ) -> Option<bool> {
+        self.acl.revoke_role_with_memo(role, account_id, memo)
+    }
+
+    pub fn acl_swap_role(&mut self, account_id: &AccountId, from: Role, to: Role) -> Option<bool> {
+        self.acl.swap_role(account_id, from, to)
+    }
+
+    pub fn acl_revoke_role_from_many(
+        &mut self,
+        role: Role,
+        account_ids: Vec<AccountId>,
+    ) -> Option<Vec<bool>> {
+        self.acl.revoke_role_from_many(role, &account_ids)
     }
 
     pub fn acl_renounce_role(&mut self, role: Role) -> bool {
         self.acl.renounce_role(role)
     }
 
-    pub fn acl_get_admins(&self, role: Role, skip: usize, limit: usize) -> Vec<AccountId> {
-        self.acl.get_bearers(role.admin().into(), skip, limit)
+    pub fn acl_set_private_enumeration(&mut self, enabled: bool) -> Option<bool> {
+        self.acl.set_private_enumeration(enabled)
     }
 
-    pub fn acl_get_role_grantees(&self, role: Role, skip: usize, limit: usize) -> Vec<AccountId> {
-        self.acl.get_bearers(role.into(), skip, limit)
+    pub fn acl_set_event_masks(&mut self, enabled: bool) -> Option<bool> {
+        self.acl.set_event_masks(enabled)
     }
-}
 
-/// Represents admin permissions for roles. Variant `Super` grants global admin
-/// permissions, each following variant grants admin permissions for the `Role`
-/// with the corresponding name.
-#[derive(Copy, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize)]
-#[serde(crate = "near_sdk::serde")]
-#[repr(u8)]
-enum AclAdmin {
-    Super,
-    L1,
-    L2,
-    L3,
-}
+    pub fn acl_set_event_prefix(&mut self, prefix: String) -> Option<bool> {
+        self.acl.set_event_prefix(prefix)
+    }
 
-impl From<Role> for AclAdmin {
-    fn from(value: Role) -> Self {
-        match value {
-            Role::L1 => AclAdmin::L1,
-            Role::L2 => AclAdmin::L2,
-            Role::L3 => AclAdmin::L3,
-        }
+    pub fn acl_set_event_version(&mut self, version: Option<String>) -> Option<bool> {
+        self.acl.set_event_version(version)
     }
-}
 
-impl Role {
-    /// Returns the `AclAdmin` variant responsible for a `Role`.
-    fn admin(self) -> AclAdmin {
-        AclAdmin::from(self)
+    pub fn acl_event_version(&self) -> String {
+        self.acl.event_version().to_string()
     }
-}
 
-bitflags! {
-    /// Flags that represent permissions in a bitmask.
-    ///
-    /// If a flag's binary value is `1 << n` with even `n` it represents an
-    /// `AclAdmin` role. Otherwise (`n` is odd), the flag represents a regular
-    /// `Role`.
-    ///
-    /// Bitmasks allow efficiently checking for multiple permissions.
-    #[derive(BorshDeserialize, BorshSerialize)]
-    struct AclPermissions: u128 {
-        const SUPER_ADMIN = 0b00000001; // 01u128 == 1 << 0
-        const L1 = 0b00000010;          // 02u128 == 1 << 1
-        const L1_ADMIN = 0b00000100;    // 04u128 == 1 << 2
-        const L2 = 0b00001000;          // 08u128 == 1 << 3
-        const L2_ADMIN = 0b00010000;    // 16u128 == 1 << 4
-        const L3 = 0b00100000;          // 32u128 == 1 << 5
-        const L3_ADMIN = 0b01000000;    // 64u128 == 1 << 6
+    #[cfg(feature = "event-history")]
+    pub fn acl_set_event_history_capacity(&mut self, capacity: u64) -> Option<bool> {
+        self.acl.set_event_history_capacity(capacity)
     }
-}
 
-const MAX_BITFLAG_SHIFT: u8 = 127; // `AclPermissions` is u128
+    #[cfg(feature = "event-history")]
+    pub fn acl_get_recent_events(&self, skip: u64, limit: u64) -> Vec<StoredAclEvent> {
+        self.acl
+            .recent_events(Self::to_usize(skip), Self::to_usize(limit))
+    }
 
-impl From<Role> for AclPermissions {
-    fn from(value: Role) -> Self {
-        // `+1` since flags for `Role` have a bit shifted by an odd number.
-        let shift = (value as u8 * 2) + 1;
-        require!(shift <= MAX_BITFLAG_SHIFT, "Role is out of bounds");
-        AclPermissions::from_bits(1u128 << shift)
-            .unwrap_or_else(|| env::panic_str("Failed to convert Role"))
+    pub fn acl_set_combine_grant_events(&mut self, enabled: bool) -> Option<bool> {
+        self.acl.set_combine_grant_events(enabled)
     }
-}
 
-impl From<AclAdmin> for AclPermissions {
-    fn from(value: AclAdmin) -> Self {
-        // Flags for `AclAdmin` have a bit shifted by an even number.
-        let shift = value as u8 * 2;
-        require!(shift <= MAX_BITFLAG_SHIFT, "AclAdmin is out of bounds");
-        AclPermissions::from_bits(1u128 << shift)
-            .unwrap_or_else(|| env::panic_str("Failed to convert AclAdmin"))
+    pub fn acl_set_auto_grant_role(&mut self, role: Option<Role>) -> Option<bool> {
+        self.acl.set_auto_grant_role(role)
     }
-}
 
-#[derive(BorshDeserialize, BorshSerialize)]
-struct Acl {
-    /// Stores permissions per account.
-    permissions: UnorderedMap<AccountId, AclPermissions>,
-    /// Stores the set of accounts that bear a permission.
-    bearers: UnorderedMap<AclPermissions, UnorderedSet<AccountId>>,
-}
+    pub fn acl_set_role_display_order(&mut self, order: Option<Vec<Role>>) -> Option<bool> {
+        self.acl.set_role_display_order(order)
+    }
 
-// TODO allow devs to specify another prefix
-/// Not to be used directly. Create prefixes with [`acl_new_storage_prefix`].
-const ACL_STORAGE_PREFIX: &[u8; 4] = b"_acl";
+    pub fn acl_get_role_display_order(&self) -> Vec<Role> {
+        self.acl.role_display_order()
+    }
 
-/// Returns a new prefix by prepending `specifier` to [`ACL_COLLECTIONS_PREFIX`].
-fn acl_new_storage_prefix(specifier: AclStorageKeys) -> Vec<u8> {
-    let extra_bytes = specifier
-        .try_to_vec()
-        .unwrap_or_else(|_| env::panic_str("Failed to serialize storage key"));
-    [ACL_STORAGE_PREFIX, extra_bytes.as_slice()].concat()
-}
+    pub fn acl_set_role_label(&mut self, role: Role, label: String) -> Option<bool> {
+        self.acl.set_role_label(role, label)
+    }
 
-/// Used to make storage prefixes unique.
-#[derive(BorshSerialize)]
-enum AclStorageKeys {
-    Permissions,
-    Bearers,
-    BearersSet { permission: AclPermissions },
-}
+    pub fn acl_get_role_label(&self, role: Role) -> Option<String> {
+        self.acl.role_label(role)
+    }
 
-impl Acl {
-    fn new() -> Self {
-        Self {
-            permissions: UnorderedMap::new(acl_new_storage_prefix(AclStorageKeys::Permissions)),
-            bearers: UnorderedMap::new(acl_new_storage_prefix(AclStorageKeys::Bearers)),
-        }
+    #[handle_result]
+    pub fn acl_get_admins(
+        &self,
+        role: Role,
+        skip: u64,
+        limit: u64,
+    ) -> Result<AclPage<AccountId>, AclError> {
+        self.acl.get_bearers(
+            role.admin_flag(),
+            Self::to_usize(skip),
+            Self::to_usize(limit),
+        )
     }
 
-    fn new_bearers_set(permission: AclPermissions) -> UnorderedSet<AccountId> {
-        UnorderedSet::new(acl_new_storage_prefix(AclStorageKeys::BearersSet {
-            permission,
-        }))
+    #[handle_result]
+    pub fn acl_get_grantees(
+        &self,
+        role: Role,
+        skip: u64,
+        limit: u64,
+    ) -> Result<AclPage<AccountId>, AclError> {
+        self.acl
+            .get_bearers(role.into(), Self::to_usize(skip), Self::to_usize(limit))
     }
 
-    /// Returns the permissions of `account_id`. If there are no permissions
-    /// stored for `account_id`, it returns an empty, newly initialized set of
-    /// permissions.
-    fn get_or_init_permissions(&self, account_id: &AccountId) -> AclPermissions {
-        match self.permissions.get(account_id) {
-            Some(permissions) => permissions,
-            None => AclPermissions::empty(),
-        }
+    #[handle_result]
+    pub fn acl_export_permissions(
+        &self,
+        skip: u64,
+        limit: u64,
+    ) -> Result<AclPage<(AccountId, U128)>, AclError> {
+        let page = self
+            .acl
+            .export_permissions(Self::to_usize(skip), Self::to_usize(limit))?;
+        Ok(page.map(|(account_id, permissions)| (account_id, U128(permissions.bits()))))
     }
 
-    /// Returns a `bool` indicating if `account_id` is an admin for `role`.
-    ///
-    /// Note that `AclAdmin::Super` grants admin rights for _every_ role. Hence,
-    /// if `account_id` has the corresponding permissions
-    /// [`AclPermissions::SUPER_ADMIN`], this function returns true for every
-    /// `Role`.
-    fn is_admin(&self, role: Role, account_id: &AccountId) -> bool {
-        let permissions = {
-            match self.permissions.get(account_id) {
-                Some(permissions) => permissions,
-                None => return false,
-            }
-        };
-        permissions.contains(AclPermissions::SUPER_ADMIN)
-            || permissions.contains(role.admin().into())
+    pub fn acl_count_admins(&self, role: Role) -> u64 {
+        self.acl.count_admins(role)
     }
 
-    /// Adds `account_id` the of admins for `role`, given that the
-    /// predecessor is an admin for `role`. Returns `Some(bool)` indicating
-    /// whether `account_id` has gained new admin permissions.
-    ///
-    /// If the predecessor is not and admin for `role`, `account_id` is not
-    /// added to the set of admins and `None` is returned.
-    fn add_admin(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        // TODO discuss: two lookups happen here: is_admin() + add_admin_unchecked().
-        // What's more important: DRY+readability or micro optimization (avoid methods
-        // to bring the number of lookups down to one)? Same at other places which
-        // call `is_admin()` before doing a modifications.
-        if !self.is_admin(role, &env::predecessor_account_id()) {
-            return None;
-        }
-        Some(self.add_admin_unchecked(role, account_id))
+    pub fn acl_count_grantees(&self, role: Role) -> u64 {
+        self.acl.count_grantees(role)
     }
 
-    /// Grants admin permissions for `role` to `account_id`, __without__
-    /// checking permissions of the predecessor.
-    ///
-    /// Returns whether `account_id` was newly added to the admins for `role`.
-    fn add_admin_unchecked(&mut self, role: Role, account_id: &AccountId) -> bool {
-        let flag: AclPermissions = role.admin().into();
-        let mut permissions = self.get_or_init_permissions(account_id);
-
-        let is_new_admin = !permissions.contains(flag);
-        if is_new_admin {
-            permissions.insert(flag);
-            self.permissions.insert(account_id, &permissions);
-            self.add_bearer(flag, account_id);
-            AclEvent::new_from_env(AclEventId::AdminAdded, role, account_id.clone()).emit();
-        }
+    pub fn acl_register_role(&mut self, name: String) -> Option<bool> {
+        self.acl.register_role(name)
+    }
 
-        is_new_admin
+    #[handle_result]
+    pub fn acl_has_role_by_name(
+        &self,
+        name: String,
+        account_id: &AccountId,
+    ) -> Result<bool, AclError> {
+        self.acl.has_role_by_name(&name, account_id)
     }
 
-    /// Revoke admin permissions for `role` from `account_id`. If the
-    /// predecessor is an admin for `role`, it returns `Some<bool>` indicating
-    /// whether `account_id` was an admin.
-    ///
-    /// If the predecessor is not an admin for `role`, it returns `None`
-    /// permissions are not modified.
-    fn revoke_admin(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        if !self.is_admin(role, &env::predecessor_account_id()) {
-            return None;
-        }
-        Some(self.revoke_admin_unchecked(role, account_id))
+    #[handle_result]
+    pub fn acl_grant_role_by_name(
+        &mut self,
+        name: String,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        self.acl.grant_role_by_name(&name, account_id)
     }
 
-    /// Revokes admin rights for `role` from the calling account. Returns
-    /// whether the caller was an admin for `role`.
-    fn renounce_admin(&mut self, role: Role) -> bool {
-        self.revoke_admin_unchecked(role, &env::predecessor_account_id())
+    #[handle_result]
+    pub fn acl_revoke_role_by_name(
+        &mut self,
+        name: String,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        self.acl.revoke_role_by_name(&name, account_id)
     }
 
-    /// Revokes admin rights for `role` from `account_id` without checking any
-    /// permissions. Returns whether `account_id` was an admin for `role`.
-    fn revoke_admin_unchecked(&mut self, role: Role, account_id: &AccountId) -> bool {
-        let flag: AclPermissions = role.admin().into();
-        let mut permissions = self.get_or_init_permissions(account_id);
+    #[handle_result]
+    pub fn acl_add_admin_by_name(
+        &mut self,
+        name: String,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        self.acl.add_admin_by_name(&name, account_id)
+    }
 
-        let was_admin = permissions.contains(flag);
-        if !was_admin {
-            permissions.remove(flag);
-            self.permissions.insert(account_id, &permissions);
-            self.remove_bearer(flag, account_id);
-            AclEvent::new_from_env(AclEventId::AdminRevoked, role, account_id.clone()).emit();
-        }
+    pub fn acl_define_group(&mut self, name: String, roles: Vec<Role>) -> Option<bool> {
+        self.acl.define_group(name, &roles)
+    }
+
+    #[handle_result]
+    pub fn acl_group_roles(&self, name: String) -> Result<Vec<Role>, AclError> {
+        self.acl.group_roles(&name)
+    }
+
+    #[handle_result]
+    pub fn acl_grant_group(
+        &mut self,
+        name: String,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        self.acl.grant_group(&name, account_id)
+    }
+
+    #[handle_result]
+    pub fn acl_revoke_group(
+        &mut self,
+        name: String,
+        account_id: &AccountId,
+    ) -> Result<Option<bool>, AclError> {
+        self.acl.revoke_group(&name, account_id)
+    }
+
+    pub fn acl_set_role_mode(&mut self, role: Role, mode: AclRoleMode) -> Option<bool> {
+        self.acl.set_role_mode(role, mode)
+    }
+
+    pub fn acl_role_mode(&self, role: Role) -> AclRoleMode {
+        self.acl.role_mode(role)
+    }
+
+    pub fn acl_set_role_implications(&mut self, role: Role, implied: Vec<Role>) -> Option<bool> {
+        self.acl.set_role_implications(role, &implied)
+    }
+
+    pub fn acl_role_implications(&self, role: Role) -> Vec<Role> {
+        self.acl.role_implications(role)
+    }
 
-        was_admin
+    pub fn acl_set_method_requirement(
+        &mut self,
+        method: String,
+        roles: Vec<Role>,
+        require_all: bool,
+    ) -> Option<bool> {
+        let requirement = AclRequirement::from_roles(&roles, require_all);
+        self.acl.set_method_requirement(method, requirement)
     }
 
-    /// Returns whether `account_id` has been granted `role`.
-    fn has_role(&self, role: Role, account_id: &AccountId) -> bool {
-        match self.permissions.get(account_id) {
-            Some(permissions) => permissions.contains(role.into()),
-            None => false,
+    /// Returns a page of the accounts which may currently call `method`, see
+    /// `Acl::get_authorized_accounts`. At most 100 accounts are returned per
+    /// call.
+    #[handle_result]
+    pub fn acl_method_authorized_accounts(
+        &self,
+        method: String,
+        skip: u64,
+        limit: u64,
+    ) -> Result<Vec<AccountId>, AclError> {
+        let requirement = self
+            .acl
+            .method_requirement(&method)
+            .ok_or(AclError::UnguardedMethod { method })?;
+        self.acl
+            .get_authorized_accounts(requirement, Self::to_usize(skip), Self::to_usize(limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "events")]
+    use crate::test_utils::logged_event_names;
+    use crate::test_utils::set_predecessor;
+    use near_sdk::collections::{UnorderedMap, UnorderedSet};
+    use near_sdk::test_utils::accounts;
+
+    /// Writes the state of a contract deployed with version 0, where
+    /// `accounts(0)` is admin for every role and `accounts(1)` has `Role::L1`.
+    fn write_state_v0(counter: u64) {
+        // Storage keys of version 0 are `_acl` followed by the variant index of
+        // the collection and, for sets of bearers, by the permission.
+        let mut permissions = UnorderedMap::<AccountId, AclPermissions>::new(b"_acl\x00".to_vec());
+        let mut bearers =
+            UnorderedMap::<AclPermissions, UnorderedSet<AccountId>>::new(b"_acl\x01".to_vec());
+        let admin_flags = [
+            AclPermissions::L1_ADMIN,
+            AclPermissions::L2_ADMIN,
+            AclPermissions::L3_ADMIN,
+        ];
+        let entries = admin_flags
+            .iter()
+            .map(|flag| (accounts(0), *flag))
+            .chain([(accounts(1), AclPermissions::L1)]);
+        for (account_id, flag) in entries {
+            let account_permissions = permissions
+                .get(&account_id)
+                .unwrap_or(AclPermissions::empty())
+                | flag;
+            permissions.insert(&account_id, &account_permissions);
+            let prefix = [b"_acl\x02".as_slice(), &flag.bits().to_le_bytes()].concat();
+            let mut set = bearers
+                .get(&flag)
+                .unwrap_or_else(|| UnorderedSet::new(prefix));
+            set.insert(&account_id);
+            bearers.insert(&flag, &set);
         }
+        env::state_write(&(counter, permissions, bearers));
     }
 
-    /// Grants `role` to `account_id`, given that the predecessor is an admin
-    /// for `role`. Returns `Some(bool)` indicating wheter `role` was newly
-    /// granted to `account_id`.
-    ///
-    /// If the predecessor is not an admin for `role`, `account_id` is not
-    /// granted the role and `None` is returned.
-    fn grant_role(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        if !self.is_admin(role, &env::predecessor_account_id()) {
-            return None;
+    #[test]
+    fn migrate_from_v0() {
+        set_predecessor(accounts(0));
+        write_state_v0(7);
+
+        let contract = Counter::migrate();
+        assert_eq!(contract.get_counter(), 7);
+        assert!(!contract.is_paused());
+        assert_eq!(
+            env::storage_read(STATE_VERSION_KEY),
+            Some(vec![STATE_VERSION])
+        );
+        for role in Role::all() {
+            assert!(contract.acl_is_admin(role, &accounts(0)));
         }
-        Some(self.grant_role_unchecked(role, account_id))
+        assert!(contract.acl_has_role(Role::L1, &accounts(1)));
+        assert_eq!(
+            contract.acl_get_permissions(&accounts(1)),
+            U128(AclPermissions::L1.bits())
+        );
+        assert_eq!(
+            contract.acl_get_grantees(Role::L1, 0, 10).unwrap().items,
+            vec![accounts(1)]
+        );
+        // Accounts are indexed, since they could be enumerated in version 0.
+        let exported = contract.acl_export_permissions(0, 10).unwrap().items;
+        assert_eq!(exported.len(), 2);
+        // The entries of the map of version 0 are removed.
+        assert!(!env::storage_has_key(
+            b"_acl\x00k\x00\x00\x00\x00\x00\x00\x00\x00"
+        ));
+
+        // Migrating again leaves the state unchanged.
+        env::state_write(&contract);
+        let contract = Counter::migrate();
+        assert!(contract.acl_has_role(Role::L1, &accounts(1)));
+        assert_eq!(contract.get_counter(), 7);
     }
 
-    /// Grants `role` to `account_id` __without__ checking any permissions.
-    /// Returns whether `role` was newly granted to `account_id`.
-    fn grant_role_unchecked(&mut self, role: Role, account_id: &AccountId) -> bool {
-        let flag: AclPermissions = role.into();
-        let mut permissions = self.get_or_init_permissions(account_id);
+    #[test]
+    fn migrated_admin_can_grant() {
+        set_predecessor(accounts(0));
+        write_state_v0(0);
+        let mut contract = Counter::migrate();
 
-        let is_new_grantee = !permissions.contains(flag);
-        if is_new_grantee {
-            permissions.insert(flag);
-            self.permissions.insert(account_id, &permissions);
-            self.add_bearer(flag, account_id);
-            AclEvent::new_from_env(AclEventId::RoleGranted, role, account_id.clone()).emit();
-        }
+        assert_eq!(contract.acl_grant_role(Role::L2, &accounts(2)), Some(true));
+        assert!(contract.acl_has_role(Role::L2, &accounts(2)));
+        assert_eq!(contract.acl_count_grantees(Role::L1), 1);
+    }
 
-        is_new_grantee
+    #[test]
+    fn new_seeds_no_super_admin() {
+        set_predecessor(accounts(0));
+        let contract = Counter::new();
+        assert!(!contract.acl_has_super_admin());
+        for role in Role::all() {
+            assert!(contract.acl_is_admin(role, &accounts(0)));
+        }
     }
 
-    /// Revoke `role` from `account_id`. If the predecessor is an admin for
-    /// `role`, it returns `Some(bool)` indicating whether `account_id` was a
-    /// grantee of `role`.
-    ///
-    /// If the predecessor is not an admin for `role`, it returns `None` and
-    /// permissions are not modified.
-    fn revoke_role(&mut self, role: Role, account_id: &AccountId) -> Option<bool> {
-        if !self.is_admin(role, &env::predecessor_account_id()) {
-            return None;
+    #[test]
+    fn new_with_super_admin_seeds_the_named_account() {
+        set_predecessor(accounts(0));
+        let contract = Counter::new_with_super_admin(accounts(1));
+        assert!(contract.acl_is_super_admin(&accounts(1)));
+        assert!(!contract.acl_is_super_admin(&accounts(0)));
+        for role in Role::all() {
+            assert!(contract.acl_is_admin(role, &accounts(1)));
+            assert!(contract.acl_is_admin(role, &accounts(0)));
         }
-        Some(self.revoke_role_unchecked(role, account_id))
     }
 
-    /// Revokes `role` from `account_id` without checking any permissions.
-    /// Returns whether `account_id` was a grantee of `role`.
-    fn revoke_role_unchecked(&mut self, role: Role, account_id: &AccountId) -> bool {
-        let flag: AclPermissions = role.into();
-        let mut permissions = self.get_or_init_permissions(account_id);
+    /// Returns a contract initialized by `accounts(0)`, who is super admin and
+    /// the predecessor afterwards.
+    fn setup_contract() -> Counter {
+        set_predecessor(accounts(0));
+        Counter::new_with_super_admin(accounts(0))
+    }
+
+    #[test]
+    fn method_authorized_accounts_of_foo4() {
+        let mut contract = setup_contract();
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        contract.acl_grant_role(Role::L1, &accounts(2));
+        contract.acl_grant_role(Role::L3, &accounts(2));
+        contract.acl_grant_role(Role::L3, &accounts(3));
+
+        let authorized = contract
+            .acl_method_authorized_accounts("foo4".to_string(), 0, 10)
+            .unwrap();
+        assert_eq!(authorized, vec![accounts(2)]);
+        assert_eq!(
+            contract
+                .acl_method_authorized_accounts("foo1".to_string(), 0, 10)
+                .err(),
+            Some(AclError::UnguardedMethod {
+                method: "foo1".to_string()
+            }),
+        );
+    }
+
+    #[test]
+    fn method_authorized_accounts_use_effective_permissions() {
+        let mut contract = setup_contract();
+        // Holding L1 suffices for foo4, since L1 implies L3.
+        contract.acl_set_role_implications(Role::L1, vec![Role::L3]);
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        // Grants which are not active yet do not count.
+        contract.acl_grant_role_from(Role::L1, &accounts(2), U64(u64::MAX));
+        contract.acl_grant_role(Role::L3, &accounts(2));
+        contract.acl_grant_role(Role::L1, &accounts(3));
+        contract.acl_grant_role(Role::L3, &accounts(3));
+
+        // The limit is capped instead of failing.
+        let authorized = contract
+            .acl_method_authorized_accounts("foo4".to_string(), 0, 1000)
+            .unwrap();
+        assert_eq!(authorized, vec![accounts(1), accounts(3)]);
+    }
+
+    #[test]
+    fn join_grants_the_auto_grant_role_once() {
+        let mut contract = setup_contract();
+        assert_eq!(contract.acl_set_auto_grant_role(Some(Role::L1)), Some(true));
 
-        let was_grantee = permissions.contains(flag);
-        if was_grantee {
-            permissions.remove(flag);
-            self.permissions.insert(account_id, &permissions);
-            self.remove_bearer(flag, account_id);
-            AclEvent::new_from_env(AclEventId::RoleRevoked, role, account_id.clone()).emit();
+        set_predecessor(accounts(1));
+        assert!(contract.join());
+        assert!(contract.acl_has_role(Role::L1, &accounts(1)));
+        assert!(!contract.join());
+
+        // An account that renounced the role is not granted it again.
+        assert!(contract.acl_renounce_role(Role::L1));
+        assert!(!contract.join());
+        assert!(!contract.acl_has_role(Role::L1, &accounts(1)));
+    }
+
+    #[test]
+    fn join_without_auto_grant_role() {
+        let mut contract = setup_contract();
+        set_predecessor(accounts(1));
+        assert!(!contract.join());
+        assert_eq!(contract.acl_get_roles(&accounts(1)), vec![]);
+    }
+
+    #[test]
+    fn method_requirements_can_be_updated() {
+        let mut contract = setup_contract();
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        contract.acl_grant_role(Role::L2, &accounts(2));
+        let authorized = |contract: &Counter| {
+            contract
+                .acl_method_authorized_accounts("foo2".to_string(), 0, 10)
+                .unwrap()
+        };
+        assert_eq!(authorized(&contract), vec![accounts(2)]);
+
+        assert_eq!(
+            contract.acl_set_method_requirement("foo2".to_string(), vec![Role::L1], false),
+            Some(true),
+        );
+        assert_eq!(authorized(&contract), vec![accounts(1)]);
+        set_predecessor(accounts(1));
+        contract.foo2();
+
+        // Only super admins may update requirements.
+        assert_eq!(
+            contract.acl_set_method_requirement("foo2".to_string(), vec![Role::L2], false),
+            None,
+        );
+    }
+
+    #[test]
+    fn new_with_genesis_admin_skips_the_deployer() {
+        set_predecessor(accounts(0));
+        let contract = Counter::new_with_genesis_admin(accounts(1));
+        assert_eq!(contract.acl_get_permissions(&accounts(0)), U128(0));
+        assert!(!contract.acl_is_super_admin(&accounts(0)));
+        assert!(contract.acl_is_super_admin(&accounts(1)));
+    }
+
+    #[test]
+    fn get_grantees_returns_a_window() {
+        let mut contract = setup_contract();
+        for i in 1..5 {
+            contract.acl_grant_role(Role::L1, &accounts(i));
         }
+        contract.acl_grant_role(Role::L2, &accounts(5));
+
+        let page = contract.acl_get_grantees(Role::L1, 1, 2).unwrap();
+        assert_eq!(page.items, vec![accounts(2), accounts(3)]);
+        assert!(page.has_more);
+        let page = contract.acl_get_grantees(Role::L1, 3, 2).unwrap();
+        assert_eq!(page.items, vec![accounts(4)]);
+        assert!(!page.has_more);
+    }
+
+    #[test]
+    fn get_roles_decodes_granted_flags() {
+        let mut contract = setup_contract();
+        assert!(contract.acl_get_roles(&accounts(1)).is_empty());
 
-        was_grantee
+        contract.acl_grant_role(Role::L3, &accounts(1));
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        assert_eq!(
+            contract.acl_get_roles(&accounts(1)),
+            vec![Role::L1, Role::L3]
+        );
     }
 
-    /// Revokes `role` from the calling account. Returns whether the caller was
-    /// a grantee of `role`.
-    fn renounce_role(&mut self, role: Role) -> bool {
-        self.revoke_role_unchecked(role, &env::predecessor_account_id())
+    #[test]
+    fn get_permissions_returns_the_raw_mask() {
+        let mut contract = setup_contract();
+        assert_eq!(contract.acl_get_permissions(&accounts(1)), U128(0));
+
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        contract.acl_grant_role(Role::L2, &accounts(1));
+        let bits = contract.acl_get_permissions(&accounts(1));
+        assert_eq!(bits, U128((AclPermissions::L1 | AclPermissions::L2).bits()));
+        // The mask is encoded as a string for JSON clients.
+        assert_eq!(near_sdk::serde_json::to_string(&bits).unwrap(), "\"10\"");
     }
 
-    /// Panics if `account_id` does not have at least one of the permissions
-    /// specified in `target`.
-    fn check_any(&self, target: AclPermissions, account_id: &AccountId) {
-        let permissions = self.get_or_init_permissions(account_id);
-        // TODO check cost and output of `fmt()` for `AclPermissions`
-        require!(
-            permissions.intersects(target),
-            format!(
-                "Account {} has must have at least one role of {:?}",
-                account_id, target
-            ),
-        )
+    #[test]
+    fn on_remote_has_role_resolves_the_answer() {
+        let contract = setup_contract();
+        contract.remote_has_role("acl.near".parse().unwrap(), Role::L1, accounts(1));
+
+        assert!(contract.on_remote_has_role(Ok(true)));
+        assert!(!contract.on_remote_has_role(Ok(false)));
+        // A failed remote call is treated as the role being absent.
+        assert!(!contract.on_remote_has_role(Err(PromiseError::Failed)));
     }
 
-    /// Panics if `account_id` does not have all of the permissions specified in
-    /// `target`.
-    fn check_all(&self, target: AclPermissions, account_id: &AccountId) {
-        let permissions = self.get_or_init_permissions(account_id);
-        // TODO check cost and output of `fmt()` for `AclPermissions`
-        require!(
-            permissions.contains(target),
-            format!("Account {} must have all roles in {:?}", account_id, target,)
-        )
+    #[test]
+    fn public_acl_api() {
+        set_predecessor(accounts(0));
+        let mut acl: crate::Acl<crate::Role> = crate::Acl::new();
+        acl.add_super_admin_unchecked(&accounts(0));
+
+        assert_eq!(
+            acl.grant_role(crate::Role::L1, &accounts(1), &accounts(0)),
+            Some(true)
+        );
+        assert!(acl.has_role(crate::Role::L1, &accounts(1)));
+        assert!(acl.is_admin(crate::Role::L2, &accounts(0)));
+        acl.check_any(crate::AclPermissions::L1, &accounts(1));
+        acl.check_all(crate::AclPermissions::L1, &accounts(1));
+        assert_eq!(
+            acl.try_check_any(crate::AclPermissions::L2, &accounts(1)),
+            Err(crate::AclError::MissingAnyPermission {
+                account_id: accounts(1),
+                required: crate::AclPermissions::L2,
+            })
+        );
     }
 
-    /// Adds `account_id` to the set of `permission` bearers.
-    fn add_bearer(&mut self, permission: AclPermissions, account_id: &AccountId) {
-        let mut set = match self.bearers.get(&permission) {
-            Some(set) => set,
-            None => Self::new_bearers_set(permission),
-        };
-        if let true = set.insert(account_id) {
-            self.bearers.insert(&permission, &set);
+    #[test]
+    fn import_permissions_writes_masks() {
+        let mut contract = setup_contract();
+        let entries = vec![
+            (accounts(1), U128(AclPermissions::L1.bits())),
+            (
+                accounts(2),
+                U128((AclPermissions::L2 | AclPermissions::L2_ADMIN).bits()),
+            ),
+            (
+                accounts(3),
+                U128((AclPermissions::L1 | AclPermissions::L3).bits()),
+            ),
+        ];
+        assert_eq!(contract.acl_import_permissions(entries.clone()), Some(true));
+        for (account_id, bits) in entries {
+            assert_eq!(contract.acl_get_permissions(&account_id), bits);
         }
+        assert!(contract.acl_has_role(Role::L3, &accounts(3)));
+        assert!(contract.acl_is_admin(Role::L2, &accounts(2)));
+
+        set_predecessor(accounts(1));
+        let entries = vec![(accounts(4), U128(AclPermissions::L1.bits()))];
+        assert_eq!(contract.acl_import_permissions(entries), None);
     }
 
-    /// Removes `account_id` from the set of `permission` bearers.
-    fn remove_bearer(&mut self, permission: AclPermissions, account_id: &AccountId) {
-        let mut set = match self.bearers.get(&permission) {
-            Some(set) => set,
-            None => return, // nothing to do
-        };
-        if let true = set.remove(account_id) {
-            self.bearers.insert(&permission, &set);
+    #[test]
+    fn export_permissions_pages_through_imports() {
+        let mut contract = setup_contract();
+        let admin_bits = contract.acl_get_permissions(&accounts(0));
+        let imported = vec![
+            (accounts(1), U128(AclPermissions::L1.bits())),
+            (accounts(2), U128(AclPermissions::L2_ADMIN.bits())),
+            (accounts(3), U128(AclPermissions::L3.bits())),
+        ];
+        contract.acl_import_permissions(imported.clone());
+
+        let mut exported = vec![];
+        let mut skip = 0;
+        loop {
+            let page = contract.acl_export_permissions(skip, 2).unwrap();
+            skip += page.items.len() as u64;
+            exported.extend(page.items);
+            if !page.has_more {
+                break;
+            }
         }
+        let expected: Vec<_> = [(accounts(0), admin_bits)]
+            .into_iter()
+            .chain(imported)
+            .collect();
+        assert_eq!(exported, expected);
     }
 
-    /// Returns up to `limit` bearers of `permission`, skipping the first `skip`
-    /// items. Allows paginated retrieval of bearers.
-    fn get_bearers(&self, permission: AclPermissions, skip: usize, limit: usize) -> Vec<AccountId> {
-        let set = match self.bearers.get(&permission) {
-            Some(set) => set,
-            None => return vec![],
-        };
-        set.iter().skip(skip).take(limit).collect()
+    #[test]
+    fn effective_permissions_of_a_super_admin() {
+        let mut contract = setup_contract();
+        contract.acl_add_super_admin(&accounts(1));
+        let super_admin = AclPermissions::SUPER_ADMIN;
+        assert_eq!(
+            contract.acl_get_permissions(&accounts(1)),
+            U128(super_admin.bits())
+        );
+
+        let admin_flags =
+            AclPermissions::L1_ADMIN | AclPermissions::L2_ADMIN | AclPermissions::L3_ADMIN;
+        assert_eq!(
+            contract.acl_effective_permissions(&accounts(1)),
+            U128((super_admin | admin_flags).bits())
+        );
+        // Accounts without implied permissions have equal masks.
+        contract.acl_grant_role(Role::L2, &accounts(2));
+        assert_eq!(
+            contract.acl_effective_permissions(&accounts(2)),
+            contract.acl_get_permissions(&accounts(2))
+        );
     }
-}
 
-// TODO probably should be the near-plugins ACL standard (if we define one)
-const EVENT_STANDARD: &str = "nep279";
-const EVENT_VERSION: &str = "1.0.0";
-
-/// Represents a [NEP-297] event.
-///
-/// Using `'static &str` where possible to avoid allocations (there's only a
-/// small set of possible values for the corresponding fields).
-///
-/// [NEP-297]: https://nomicon.io/Standards/EventsFormat
-
-// TODO try using lifetime `'a` instead of `'static`.
-// TODO allow users emitting custom data together with events (in later version)
-#[derive(Serialize)]
-#[serde(crate = "near_sdk::serde")]
-struct AclEvent<R> {
-    standard: &'static str,
-    version: &'static str,
-    event: &'static str,
-    data: AclEventMetadata<R>,
-}
+    #[test]
+    fn increment_requires_l1() {
+        let mut contract = setup_contract();
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        set_predecessor(accounts(1));
+        contract.increment();
+        contract.increment();
+        assert_eq!(contract.get_counter(), 2);
+
+        set_predecessor(accounts(0));
+        contract.reset();
+        assert_eq!(contract.get_counter(), 0);
+    }
 
-impl<R> AclEvent<R>
-where
-    R: Serialize,
-{
-    /// Constructor which reads predecessor's account id from the current
-    /// environment. Parameters `role` and `account_id` are passed on to
-    /// [`AclEventMetadata`].
-    fn new_from_env(id: AclEventId, role: R, account_id: AccountId) -> Self {
-        Self {
-            standard: EVENT_STANDARD,
-            version: EVENT_VERSION,
-            event: id.name(),
-            data: AclEventMetadata {
-                role,
-                account_id,
-                predecessor: env::predecessor_account_id(),
-            },
-        }
+    #[test]
+    #[cfg(feature = "events")]
+    fn init_role_holder_grants_role_and_admin() {
+        let mut contract = setup_contract();
+        contract.acl_add_admin(Role::L2, &accounts(1));
+        set_predecessor(accounts(1));
+        assert_eq!(
+            contract.acl_init_role_holder(Role::L2, &accounts(2)),
+            Some(true)
+        );
+        assert!(contract.acl_has_role(Role::L2, &accounts(2)));
+        assert!(contract.acl_is_admin(Role::L2, &accounts(2)));
+        assert_eq!(
+            logged_event_names(),
+            vec!["acl_role_granted", "acl_admin_added"]
+        );
+
+        // Only admins for the role may call it.
+        set_predecessor(accounts(3));
+        assert_eq!(contract.acl_init_role_holder(Role::L2, &accounts(3)), None);
+        assert!(!contract.acl_has_role(Role::L2, &accounts(3)));
     }
 
-    /// Emits the event by logging to the current environment.
-    fn emit(&self) {
-        let ser = serde_json::to_string(self)
-            .unwrap_or_else(|_| env::panic_str("Failed to serialize AclEvent"));
-        env::log_str(&ser)
+    #[test]
+    #[should_panic(expected = "Only L1 grantees can increment the counter")]
+    fn increment_fails_without_l1() {
+        let mut contract = setup_contract();
+        contract.acl_grant_role(Role::L2, &accounts(1));
+        set_predecessor(accounts(1));
+        contract.increment();
     }
-}
 
-/// Events resulting from ACL actions.
-#[derive(Copy, Clone)]
-enum AclEventId {
-    AdminAdded,
-    AdminRevoked,
-    RoleGranted,
-    RoleRevoked,
-}
+    #[test]
+    #[should_panic(expected = "Only super admins can call this method")]
+    fn reset_requires_super_admin() {
+        let mut contract = setup_contract();
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        set_predecessor(accounts(1));
+        contract.reset();
+    }
 
-impl AclEventId {
-    /// Returns the name to be used in the `event` field when formatting
-    /// according to NEP-297.
-    ///
-    /// Returning `&'static str` to avoid allocations when emitting events.
-    fn name(self) -> &'static str {
-        // TODO let user change event prefix `acl_`
-        match self {
-            Self::AdminAdded => "acl_admin_added",
-            Self::AdminRevoked => "acl_admin_revoked",
-            Self::RoleGranted => "acl_role_granted",
-            Self::RoleRevoked => "acl_role_revoked",
-        }
+    #[test]
+    fn is_authorized_agrees_with_checks() {
+        let mut contract = setup_contract();
+        contract.acl_grant_role(Role::L1, &accounts(1));
+        contract.acl_grant_role(Role::L2, &accounts(1));
+        let l1_l2 = AclPermissions::L1 | AclPermissions::L2;
+        let l1_l3 = AclPermissions::L1 | AclPermissions::L3;
+
+        // Where the checks pass.
+        assert!(contract.acl_is_authorized_any(U128(l1_l3.bits()), &accounts(1)));
+        contract.acl.check_any(l1_l3, &accounts(1));
+        assert!(contract.acl_is_authorized_all(U128(l1_l2.bits()), &accounts(1)));
+        contract.acl.check_all(l1_l2, &accounts(1));
+
+        // Where the checks would panic.
+        assert!(!contract.acl_is_authorized_all(U128(l1_l3.bits()), &accounts(1)));
+        assert!(contract.acl.try_check_all(l1_l3, &accounts(1)).is_err());
+        assert!(!contract.acl_is_authorized_any(U128(l1_l2.bits()), &accounts(2)));
+        assert!(contract.acl.try_check_any(l1_l2, &accounts(2)).is_err());
     }
-}
 
-/// Metadata emitted in NEP-297 event field `data`.
-
-// TODO use references to `AccountId` (avoid cloning); if it works with serde.
-// If `Deserialize` must be derived, probably won't work (out of the box).
-#[derive(Serialize)]
-#[serde(crate = "near_sdk::serde")]
-struct AclEventMetadata<R> {
-    /// The role related to the event.
-    role: R,
-    /// The account whose permissions are affected.
-    account_id: AccountId,
-    /// The account which originated the contract call.
-    predecessor: AccountId,
+    #[test]
+    fn increment_resumes_after_unpause() {
+        let mut contract = setup_contract();
+        assert!(contract.pause());
+        assert!(contract.is_paused());
+        assert!(contract.unpause());
+        assert!(!contract.is_paused());
+        contract.increment();
+        assert_eq!(contract.get_counter(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn increment_fails_while_paused() {
+        let mut contract = setup_contract();
+        contract.pause();
+        contract.increment();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only super admins can call this method")]
+    fn pause_requires_super_admin() {
+        let mut contract = setup_contract();
+        contract.acl_add_admin(Role::L1, &accounts(1));
+        set_predecessor(accounts(1));
+        contract.pause();
+    }
+
+    #[test]
+    fn has_role_or_super_includes_super_admins() {
+        let contract = setup_contract();
+        assert!(!contract.acl_has_role(Role::L2, &accounts(0)));
+        assert!(contract.acl_has_role_or_super(Role::L2, &accounts(0)));
+        assert!(!contract.acl_has_role_or_super(Role::L2, &accounts(1)));
+    }
 }
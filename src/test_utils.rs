@@ -0,0 +1,44 @@
+//! Helpers shared by the unit tests of the crate.
+
+use near_sdk::test_utils::VMContextBuilder;
+#[cfg(feature = "events")]
+use near_sdk::{serde_json, test_utils::get_logs};
+use near_sdk::{testing_env, AccountId};
+
+/// Returns a context in which `predecessor` calls the contract. Contract
+/// storage is kept when switching contexts with `testing_env!`.
+pub(crate) fn get_context(predecessor: AccountId) -> VMContextBuilder {
+    let mut builder = VMContextBuilder::new();
+    builder
+        .current_account_id("contract.near".parse().unwrap())
+        .predecessor_account_id(predecessor);
+    builder
+}
+
+/// Makes `predecessor` the caller of subsequent contract methods.
+pub(crate) fn set_predecessor(predecessor: AccountId) {
+    testing_env!(get_context(predecessor).build());
+}
+
+/// Returns the events logged in the current context.
+#[cfg(feature = "events")]
+pub(crate) fn logged_events() -> Vec<serde_json::Value> {
+    get_logs()
+        .iter()
+        .map(|log| {
+            let json = log
+                .strip_prefix("EVENT_JSON:")
+                .expect("log is not an event");
+            serde_json::from_str(json).expect("log is not an event")
+        })
+        .collect()
+}
+
+/// Returns the names of the events logged in the current context.
+#[cfg(feature = "events")]
+pub(crate) fn logged_event_names() -> Vec<String> {
+    logged_events()
+        .iter()
+        .map(|event| event["event"].as_str().unwrap().to_string())
+        .collect()
+}
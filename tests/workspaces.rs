@@ -0,0 +1,139 @@
+//! Integration tests which deploy the contract to a local sandbox node and
+//! call it from real accounts.
+//!
+//! The contract must be built with `make build` before running these tests.
+
+use near_workspaces::{Account, Contract};
+use serde_json::json;
+
+const WASM_PATH: &str = "target/wasm32-unknown-unknown/release/acl_example.wasm";
+
+/// Deploys the contract and initializes it via `new_with_super_admin` called by
+/// `admin`, who becomes super admin and admin for every role.
+async fn setup() -> anyhow::Result<(Contract, Account)> {
+    let worker = near_workspaces::sandbox().await?;
+    let wasm = std::fs::read(WASM_PATH)
+        .map_err(|err| anyhow::anyhow!("{}: {}, run `make build` first", WASM_PATH, err))?;
+    let contract = worker.dev_deploy(&wasm).await?;
+    let admin = worker.dev_create_account().await?;
+    let outcome = admin
+        .call(contract.id(), "new_with_super_admin")
+        .args_json(json!({ "super_admin": admin.id() }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(outcome.is_success(), "{:?}", outcome);
+    Ok((contract, admin))
+}
+
+/// Calls `method` of `contract` as `caller` with the arguments `role` and
+/// `account_id`. Returns the result of the call and the names of the events it
+/// logged.
+async fn call_with_role(
+    caller: &Account,
+    contract: &Contract,
+    method: &str,
+    role: &str,
+    account_id: &Account,
+) -> anyhow::Result<(Option<bool>, Vec<String>)> {
+    let outcome = caller
+        .call(contract.id(), method)
+        .args_json(json!({ "role": role, "account_id": account_id.id() }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(outcome.is_success(), "{:?}", outcome);
+    let event_names = outcome
+        .logs()
+        .iter()
+        .map(|log| {
+            let json = log
+                .strip_prefix("EVENT_JSON:")
+                .expect("log is not an event");
+            let event: serde_json::Value = serde_json::from_str(json).expect("log is not an event");
+            event["event"].as_str().unwrap().to_string()
+        })
+        .collect();
+    Ok((outcome.json()?, event_names))
+}
+
+/// Returns the result of the view method `method` of `contract` with the
+/// arguments `role` and `account_id`.
+async fn view_with_role(
+    contract: &Contract,
+    method: &str,
+    role: &str,
+    account_id: &Account,
+) -> anyhow::Result<bool> {
+    let result = contract
+        .view(method)
+        .args_json(json!({ "role": role, "account_id": account_id.id() }))
+        .await?;
+    Ok(result.json()?)
+}
+
+#[tokio::test]
+async fn grant_and_revoke_role() -> anyhow::Result<()> {
+    let (contract, admin) = setup().await?;
+    let alice = admin
+        .create_subaccount("alice")
+        .transact()
+        .await?
+        .into_result()?;
+
+    let (result, events) =
+        call_with_role(&admin, &contract, "acl_grant_role", "L1", &alice).await?;
+    assert_eq!(result, Some(true));
+    assert_eq!(events, vec!["acl_role_granted"]);
+    assert!(view_with_role(&contract, "acl_has_role", "L1", &alice).await?);
+
+    // Granting again changes nothing and emits no event.
+    let (result, events) =
+        call_with_role(&admin, &contract, "acl_grant_role", "L1", &alice).await?;
+    assert_eq!(result, Some(false));
+    assert!(events.is_empty());
+
+    let (result, events) =
+        call_with_role(&admin, &contract, "acl_revoke_role", "L1", &alice).await?;
+    assert_eq!(result, Some(true));
+    assert_eq!(events, vec!["acl_role_revoked"]);
+    assert!(!view_with_role(&contract, "acl_has_role", "L1", &alice).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn is_admin_of_deployer() -> anyhow::Result<()> {
+    let (contract, admin) = setup().await?;
+    let alice = admin
+        .create_subaccount("alice")
+        .transact()
+        .await?
+        .into_result()?;
+
+    for role in ["L1", "L2", "L3"] {
+        assert!(view_with_role(&contract, "acl_is_admin", role, &admin).await?);
+        assert!(!view_with_role(&contract, "acl_is_admin", role, &alice).await?);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn grant_role_by_non_admin() -> anyhow::Result<()> {
+    let (contract, admin) = setup().await?;
+    let alice = admin
+        .create_subaccount("alice")
+        .transact()
+        .await?
+        .into_result()?;
+    let bob = admin
+        .create_subaccount("bob")
+        .transact()
+        .await?
+        .into_result()?;
+
+    let (result, events) = call_with_role(&alice, &contract, "acl_grant_role", "L2", &bob).await?;
+    assert_eq!(result, None);
+    assert!(events.is_empty());
+    assert!(!view_with_role(&contract, "acl_has_role", "L2", &bob).await?);
+    Ok(())
+}